@@ -0,0 +1,136 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Processes the files of a directory in parallel (see `--jobs`),
+//! and sums up what happened to them.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use rayon::prelude::*;
+
+/// What happened to a single file.
+#[derive(Debug)]
+pub enum Outcome<E> {
+    /// It was processed successfully
+    Done,
+    /// It was not processed, because its output file already exists
+    Skipped,
+    /// Processing it failed
+    Failed(E),
+}
+
+/// Runs `process` on all `items`, using `jobs` threads
+/// (0 meaning one per CPU core).
+/// If `fail_fast` is set, no further items are started
+/// after the first failure; those are left out of the result.
+/// The result is in the same order as `items`,
+/// no matter in which order they were processed.
+///
+/// # Errors
+///
+/// If the thread pool could not be created.
+pub fn run<I, E, F>(
+    items: &[I],
+    jobs: usize,
+    fail_fast: bool,
+    process: F,
+) -> Result<Vec<(&I, Outcome<E>)>, rayon::ThreadPoolBuildError>
+where
+    I: Sync,
+    E: Send,
+    F: Fn(&I) -> Outcome<E> + Sync,
+{
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(jobs).build()?;
+    log::debug!(
+        "Processing {} files in {} threads ...",
+        items.len(),
+        pool.current_num_threads()
+    );
+    let abort = AtomicBool::new(false);
+    Ok(pool.install(|| {
+        items
+            .par_iter()
+            .filter_map(|item| {
+                if abort.load(Ordering::Relaxed) {
+                    return None;
+                }
+                let outcome = process(item);
+                if fail_fast && matches!(outcome, Outcome::Failed(_)) {
+                    abort.store(true, Ordering::Relaxed);
+                }
+                Some((item, outcome))
+            })
+            .collect()
+    }))
+}
+
+/// Counts what happened to the files of a batch run.
+/// Its textual representation only depends on the outcomes,
+/// not on the order in which the files were processed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    /// What was done to the files that succeeded, e.g. "converted"
+    done_label: &'static str,
+    /// Whether files may be skipped at all
+    skippable: bool,
+    pub done: usize,
+    pub skipped: usize,
+    /// The files that failed, grouped by kind of error
+    pub failed: BTreeMap<&'static str, Vec<PathBuf>>,
+}
+
+impl Summary {
+    pub const fn new(done_label: &'static str, skippable: bool) -> Self {
+        Self {
+            done_label,
+            skippable,
+            done: 0,
+            skipped: 0,
+            failed: BTreeMap::new(),
+        }
+    }
+
+    /// Counts the outcome of a single file.
+    /// `kind` names the kind of error in case of failure.
+    pub fn add<E, K>(&mut self, file: &Path, outcome: &Outcome<E>, kind: K)
+    where
+        K: Fn(&E) -> &'static str,
+    {
+        match outcome {
+            Outcome::Done => self.done += 1,
+            Outcome::Skipped => self.skipped += 1,
+            Outcome::Failed(err) => {
+                let files = self.failed.entry(kind(err)).or_default();
+                let pos = files.partition_point(|other| other.as_path() < file);
+                files.insert(pos, file.to_path_buf());
+            }
+        }
+    }
+
+    pub fn num_failed(&self) -> usize {
+        self.failed.values().map(Vec::len).sum()
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Summary: {} {}", self.done, self.done_label)?;
+        if self.skippable {
+            write!(f, ", {} skipped (output exists)", self.skipped)?;
+        }
+        write!(f, ", {} failed", self.num_failed())?;
+        for (kind, files) in &self.failed {
+            write!(f, "\n  {kind}: {}", files.len())?;
+            for file in files {
+                write!(f, "\n    - {}", file.display())?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -28,16 +28,85 @@ pub const A_S_OKH_VERSION: char = 'o';
 pub const A_L_CONTINUE_ON_ERROR: &str = "continue";
 pub const A_S_CONTINUE_ON_ERROR: char = 'c';
 
+pub const A_L_JOBS: &str = "jobs";
+pub const A_S_JOBS: char = 'j';
+
 pub const A_L_OVERWRITE: &str = "overwrite";
 pub const A_S_OVERWRITE: char = 'o';
 
+pub const A_L_REPORT: &str = "report";
+
+pub const A_L_REPORT_FORMAT: &str = "report-format";
+
+pub const A_L_REPORT_FILE: &str = "report-file";
+
+pub const A_L_DRY_RUN: &str = "dry-run";
+pub const A_S_DRY_RUN: char = 'n';
+
+pub const A_L_DIFF: &str = "diff";
+
+pub const A_L_MANIFEST_CACHE: &str = "manifest-cache";
+
+pub const A_L_MAPPING: &str = "mapping";
+
+pub const A_L_NO_GIT_TIMESTAMP: &str = "no-git-timestamp";
+
+pub const A_L_LENIENT: &str = "lenient";
+
+pub const A_L_CHECK_FILES: &str = "check-files";
+
+pub const A_L_CHECK_URLS: &str = "check-urls";
+
+pub const A_L_URL_TIMEOUT: &str = "url-timeout";
+
+pub const A_L_URL_JOBS: &str = "url-jobs";
+
+pub const A_L_URL_CACHE: &str = "url-cache";
+
+pub const A_L_NO_URL_CACHE: &str = "no-url-cache";
+
+pub const A_L_URL_CACHE_TTL: &str = "url-cache-ttl";
+/// More than a century; keeps the TTL representable in seconds
+const URL_CACHE_TTL_MAX_HOURS: u64 = 1_000_000;
+
+pub const A_L_URL_SEVERITY: &str = "url-severity";
+
+pub const A_L_CITATION: &str = "citation";
+
 pub const SC_N_GENERATE: &str = "gen";
 
+pub const SC_N_SANITIZE: &str = "sanitize";
+
+pub const A_L_IN_PLACE: &str = "in-place";
+pub const A_S_IN_PLACE: char = 'i';
+
+pub const A_L_UPDATE: &str = "update";
+pub const A_S_UPDATE: char = 'u';
+
+pub const A_L_OWNERSHIP: &str = "ownership";
+
+pub const A_L_FROM: &str = "from";
+
+pub const A_L_TO: &str = "to";
+
+/// The input or output path denoting stdin or stdout
+pub const STD_STREAM: &str = "-";
+
+/// The names of the formats that may be converted from and/or to
+pub const FORMAT_NAMES: [&str; 9] = [
+    "v1", "losh", "rdf", "json", "jsonld", "krawler", "oshwa", "cff", "codemeta",
+];
+
+/// The names of the formats that may be validated
+pub const FORMAT_NAMES_VALIDATABLE: [&str; 3] = ["v1", "losh", "json"];
+
 pub const OKH_MANIFEST_FILE_NAME: &str = "okh.toml";
 
 fn arg_input() -> Arg {
     Arg::new(A_P_INPUT)
-        .help("The input file or dir path")
+        .help(formatcp!(
+            "The input file or dir path, or '{STD_STREAM}' for stdin"
+        ))
         .num_args(1)
         .value_name("INPUT")
         .value_hint(ValueHint::AnyPath)
@@ -48,7 +117,7 @@ fn arg_input() -> Arg {
 
 fn arg_output() -> Arg {
     Arg::new(A_P_OUTPUT)
-        .help("The output file or dir path")
+        .help(formatcp!("The output file or dir path, or '{STD_STREAM}' for stdout; if the input is stdin, this defaults to stdout"))
         .num_args(1)
         .value_name("OUTPUT")
         .value_hint(ValueHint::AnyPath)
@@ -82,12 +151,23 @@ fn arg_quiet() -> Arg {
 
 fn subcom_convert() -> Command {
     Command::new(SC_N_CONVERT)
-        .about("Converts one format into an other (currently only OKH-v1 to OKH-LOSH)")
+        .about("Converts one format into an other (currently OKH-v1 to OKH-LOSH and back, and OKH-LOSH to RDF/Turtle, JSON or JSON-LD and back, OKH-LOSH to 'CITATION.cff' and 'codemeta.json', and LOSH krawler YAML and OSHWA certification records to OKH-LOSH, selected by the input and output file extensions ('.ttl', '.json', '.jsonld') or by --from and --to; '-' denotes stdin/stdout)")
         .arg(arg_input().index(1))
         .arg(arg_output().index(2))
+        .arg(arg_okhv_input())
+        .arg(arg_from())
+        .arg(arg_to())
         .arg(arg_recursive())
         .arg(arg_continue_on_error())
+        .arg(arg_jobs())
         .arg(arg_overwrite())
+        .arg(arg_dry_run())
+        .arg(arg_diff())
+        .arg(arg_report())
+        .arg(arg_manifest_cache())
+        .arg(arg_mapping())
+        .arg(arg_no_git_timestamp())
+        .arg(arg_lenient())
 }
 
 fn arg_okhv() -> Arg {
@@ -100,6 +180,47 @@ fn arg_okhv() -> Arg {
         .action(ArgAction::Set)
 }
 
+fn arg_okhv_input() -> Arg {
+    Arg::new(A_L_OKH_VERSION)
+        .help("The OKH version of the input file(s); if not given, it is figured out from the input file extension, or in case of an input dir, defaults to v1. 'krawler' denotes the YAML produced by the LOSH krawler, and 'oshwa' an OSHWA certification record (JSON); these always have to be given explicitly")
+        .num_args(1)
+        .long(A_L_OKH_VERSION)
+        .value_parser(["v1", "losh", "krawler", "oshwa"])
+        .action(ArgAction::Set)
+}
+
+fn arg_from() -> Arg {
+    Arg::new(A_L_FROM)
+        .help("The format of the input file(s); if not given, it is figured out from the input file extension, or in case of an input dir, defaults to v1. Required when reading from stdin")
+        .num_args(1)
+        .long(A_L_FROM)
+        .value_name("FORMAT")
+        .value_parser(FORMAT_NAMES)
+        .conflicts_with(A_L_OKH_VERSION)
+        .action(ArgAction::Set)
+}
+
+fn arg_from_validate() -> Arg {
+    Arg::new(A_L_FROM)
+        .help("The format of the input file(s); if not given, it is figured out from the input file extension. Required when reading from stdin")
+        .num_args(1)
+        .long(A_L_FROM)
+        .value_name("FORMAT")
+        .value_parser(FORMAT_NAMES_VALIDATABLE)
+        .conflicts_with(A_L_OKH_VERSION)
+        .action(ArgAction::Set)
+}
+
+fn arg_to() -> Arg {
+    Arg::new(A_L_TO)
+        .help("The format to convert to; if not given, it is figured out from the output file name or extension, or defaults to losh (or v1, if the input is losh)")
+        .num_args(1)
+        .long(A_L_TO)
+        .value_name("FORMAT")
+        .value_parser(FORMAT_NAMES)
+        .action(ArgAction::Set)
+}
+
 fn arg_continue_on_error() -> Arg {
     Arg::new(A_L_CONTINUE_ON_ERROR)
         .help("If the input path is a directory, continue processing further files, even after an error")
@@ -108,6 +229,18 @@ fn arg_continue_on_error() -> Arg {
         .action(ArgAction::SetTrue)
 }
 
+fn arg_jobs() -> Arg {
+    Arg::new(A_L_JOBS)
+        .help("If the input path is a directory, process this many files in parallel; 0 means one per CPU core")
+        .num_args(1)
+        .short(A_S_JOBS)
+        .long(A_L_JOBS)
+        .value_name("NUM")
+        .value_parser(value_parser!(usize))
+        .default_value("0")
+        .action(ArgAction::Set)
+}
+
 fn arg_overwrite() -> Arg {
     Arg::new(A_L_OVERWRITE)
         .help("If the output file already exists, overwrite it, instead of skipping the conversion")
@@ -116,18 +249,243 @@ fn arg_overwrite() -> Arg {
         .action(ArgAction::SetTrue)
 }
 
+fn arg_dry_run() -> Arg {
+    Arg::new(A_L_DRY_RUN)
+        .help("Do not write any files, but print what would be written to stdout")
+        .short(A_S_DRY_RUN)
+        .long(A_L_DRY_RUN)
+        .action(ArgAction::SetTrue)
+}
+
+fn arg_diff() -> Arg {
+    Arg::new(A_L_DIFF)
+        .help("Do not write any files, but print a field-level diff between the existing and the newly produced content to stdout; existing files are compared even without --overwrite")
+        .long(A_L_DIFF)
+        .action(ArgAction::SetTrue)
+}
+
+fn arg_update() -> Arg {
+    Arg::new(A_L_UPDATE)
+        .help("If the manifest file already exists, update it: re-derive only the machine-owned fields (by default: version, timestamp, image, source, export and part), and keep the human-owned ones as they are")
+        .short(A_S_UPDATE)
+        .long(A_L_UPDATE)
+        .conflicts_with(A_L_OVERWRITE)
+        .action(ArgAction::SetTrue)
+}
+
+fn arg_ownership() -> Arg {
+    Arg::new(A_L_OWNERSHIP)
+        .help("A TOML file listing the machine-owned fields to re-derive when updating, e.g.: machine = [\"version\", \"timestamp\", \"image\"]")
+        .num_args(1)
+        .long(A_L_OWNERSHIP)
+        .value_name("OWNERSHIP_FILE")
+        .value_hint(ValueHint::FilePath)
+        .value_parser(value_parser!(PathBuf))
+        .requires(A_L_UPDATE)
+        .action(ArgAction::Set)
+}
+
+fn arg_no_git_timestamp() -> Arg {
+    Arg::new(A_L_NO_GIT_TIMESTAMP)
+        .help("Do not use the last commit time of the git repo containing the input file as timestamp, but only the dates from within the manifest; useful for reproducible results")
+        .long(A_L_NO_GIT_TIMESTAMP)
+        .action(ArgAction::SetTrue)
+}
+
+fn arg_lenient() -> Arg {
+    Arg::new(A_L_LENIENT)
+        .help("Convert OKH v1 manifests even if some of their properties can not be read (e.g. because they are of the wrong type), converting numbers and booleans where text is expected, and leaving out the rest; each one is logged as a warning, and those left out are noted in the conversion report")
+        .long(A_L_LENIENT)
+        .action(ArgAction::SetTrue)
+}
+
+fn arg_check_files() -> Arg {
+    Arg::new(A_L_CHECK_FILES)
+        .help("Also check that the files referred to by OKH LOSH manifests exist, relative to the directory of the manifest, and that none of them point outside of it (through '..')")
+        .long(A_L_CHECK_FILES)
+        .action(ArgAction::SetTrue)
+}
+
+fn arg_check_urls() -> Arg {
+    Arg::new(A_L_CHECK_URLS)
+        .help("Also check that the URLs referred to by OKH LOSH manifests ('repo', 'release', 'fork-of', 'attestation' and the software releases) are reachable, using HEAD and if that fails, GET requests")
+        .long(A_L_CHECK_URLS)
+        .action(ArgAction::SetTrue)
+}
+
+fn arg_url_timeout() -> Arg {
+    Arg::new(A_L_URL_TIMEOUT)
+        .help("How long to wait for a server to respond when checking URLs")
+        .num_args(1)
+        .long(A_L_URL_TIMEOUT)
+        .value_name("SECONDS")
+        .value_parser(value_parser!(u64))
+        .default_value("10")
+        .action(ArgAction::Set)
+        .requires(A_L_CHECK_URLS)
+}
+
+fn arg_url_jobs() -> Arg {
+    Arg::new(A_L_URL_JOBS)
+        .help("How many URLs to check in parallel; 0 means one per CPU core")
+        .num_args(1)
+        .long(A_L_URL_JOBS)
+        .value_name("NUM")
+        .value_parser(value_parser!(usize))
+        .default_value("8")
+        .action(ArgAction::Set)
+        .requires(A_L_CHECK_URLS)
+}
+
+fn arg_url_cache() -> Arg {
+    Arg::new(A_L_URL_CACHE)
+        .help("The file in which the results of checking URLs are cached; defaults to 'okh-tool/url-check.json' within the users cache dir ($XDG_CACHE_HOME or ~/.cache)")
+        .num_args(1)
+        .long(A_L_URL_CACHE)
+        .value_name("FILE")
+        .value_hint(ValueHint::FilePath)
+        .value_parser(value_parser!(PathBuf))
+        .action(ArgAction::Set)
+        .requires(A_L_CHECK_URLS)
+}
+
+fn arg_no_url_cache() -> Arg {
+    Arg::new(A_L_NO_URL_CACHE)
+        .help("Neither read nor write the cache of URL check results")
+        .long(A_L_NO_URL_CACHE)
+        .action(ArgAction::SetTrue)
+        .requires(A_L_CHECK_URLS)
+        .conflicts_with(A_L_URL_CACHE)
+}
+
+fn arg_url_cache_ttl() -> Arg {
+    Arg::new(A_L_URL_CACHE_TTL)
+        .help("For how long a cached URL check result is trusted; failures to connect are never cached")
+        .num_args(1)
+        .long(A_L_URL_CACHE_TTL)
+        .value_name("HOURS")
+        .value_parser(value_parser!(u64).range(..=URL_CACHE_TTL_MAX_HOURS))
+        .default_value("24")
+        .action(ArgAction::Set)
+        .requires(A_L_CHECK_URLS)
+}
+
+fn arg_url_severity() -> Arg {
+    Arg::new(A_L_URL_SEVERITY)
+        .help("Whether unreachable URLs are only warned about, or make the manifest invalid")
+        .num_args(1)
+        .long(A_L_URL_SEVERITY)
+        .value_parser(["warning", "error"])
+        .default_value("warning")
+        .action(ArgAction::Set)
+        .requires(A_L_CHECK_URLS)
+}
+
+fn arg_citation() -> Arg {
+    Arg::new(A_L_CITATION)
+        .help("Also generate 'CITATION.cff' and 'codemeta.json' files from the manifest")
+        .long(A_L_CITATION)
+        .action(ArgAction::SetTrue)
+}
+
+fn arg_in_place() -> Arg {
+    Arg::new(A_L_IN_PLACE)
+        .help("Overwrite the input file with the result")
+        .short(A_S_IN_PLACE)
+        .long(A_L_IN_PLACE)
+        .action(ArgAction::SetTrue)
+        .conflicts_with(A_P_OUTPUT)
+}
+
+fn arg_report() -> Arg {
+    Arg::new(A_L_REPORT)
+        .help("Write a conversion report (JSON) next to each output file, listing what happened to each source property")
+        .long(A_L_REPORT)
+        .action(ArgAction::SetTrue)
+}
+
+fn arg_report_format() -> Arg {
+    Arg::new(A_L_REPORT_FORMAT)
+        .help("The format of the validation report (see --report-file); 'json' is our own structure, 'sarif' is understood by many code hosting platforms, and 'junit' (XML) by most CI systems")
+        .num_args(1)
+        .long(A_L_REPORT_FORMAT)
+        .value_parser(["json", "sarif", "junit"])
+        .default_value("json")
+        .action(ArgAction::Set)
+        .requires(A_L_REPORT_FILE)
+}
+
+fn arg_report_file() -> Arg {
+    Arg::new(A_L_REPORT_FILE)
+        .help("Write a machine readable validation report to this file, listing each error found in each manifest")
+        .num_args(1)
+        .long(A_L_REPORT_FILE)
+        .value_name("FILE")
+        .value_hint(ValueHint::FilePath)
+        .value_parser(value_parser!(PathBuf))
+        .action(ArgAction::Set)
+}
+
+fn arg_manifest_cache() -> Arg {
+    Arg::new(A_L_MANIFEST_CACHE)
+        .help("A local dir mirroring remote manifests as '<host>/<url-path>', used to find the manifests of sub-parts that are not part of the local project; NOTE: Without the 'v1_non_losh' feature, the design files of OKH v1 manifests are not read, so sub-parts end up with neither source nor export files, and are always dropped")
+        .num_args(1)
+        .long(A_L_MANIFEST_CACHE)
+        .value_name("DIR")
+        .value_hint(ValueHint::DirPath)
+        .value_parser(value_parser!(PathBuf))
+        .action(ArgAction::Set)
+}
+
+fn arg_mapping() -> Arg {
+    Arg::new(A_L_MAPPING)
+        .help("A TOML file with rules overriding the built-in decisions of the OKH-v1 to OKH-LOSH conversion, e.g. the order in which the licenses are combined, or the development-stage to OTRL table")
+        .num_args(1)
+        .long(A_L_MAPPING)
+        .value_name("RULES_FILE")
+        .value_hint(ValueHint::FilePath)
+        .value_parser(value_parser!(PathBuf))
+        .action(ArgAction::Set)
+}
+
 fn subcom_validate() -> Command {
     Command::new(SC_N_VALIDATE)
-    .about("Validates manifest files for validity using JSON Schema (currently supports OKH-v1 and OKH-LOSH)")
+    .about("Validates manifest files for validity using JSON Schema (currently supports OKH-v1 and OKH-LOSH, the later as TOML or JSON; '-' denotes stdin, which requires --from)")
     .arg(arg_input().index(1))
     .arg(arg_okhv())
+    .arg(arg_from_validate())
     .arg(arg_recursive())
+    .arg(arg_jobs())
+    .arg(arg_check_files())
+    .arg(arg_check_urls())
+    .arg(arg_url_timeout())
+    .arg(arg_url_jobs())
+    .arg(arg_url_cache())
+    .arg(arg_no_url_cache())
+    .arg(arg_url_cache_ttl())
+    .arg(arg_url_severity())
+    .arg(arg_report_format())
+    .arg(arg_report_file())
 }
 
 fn subcom_generate() -> Command {
     Command::new(SC_N_GENERATE)
     .about(formatcp!("Generates a starter-manifest file ('{}', OKH-LOSH) for the project at CWD. You will need to manually replace some values within it.", OKH_MANIFEST_FILE_NAME))
     .arg(arg_overwrite())
+    .arg(arg_update())
+    .arg(arg_ownership())
+    .arg(arg_dry_run())
+    .arg(arg_diff())
+    .arg(arg_citation())
+}
+
+fn subcom_sanitize() -> Command {
+    Command::new(SC_N_SANITIZE)
+    .about(formatcp!("Cleans up a dirty OKH-v1 manifest (YAML), avoiding common pitfalls like lines left over from the template, half-filled keys, tab indentation and wrongly typed values, and lists each fix applied; '{STD_STREAM}' denotes stdin/stdout"))
+    .arg(arg_input().index(1).help(formatcp!("The input file path, or '{STD_STREAM}' for stdin")))
+    .arg(arg_output().index(2).help(formatcp!("The output file path, or '{STD_STREAM}' for stdout; defaults to stdout")))
+    .arg(arg_in_place())
+    .arg(arg_overwrite().help("If the output file already exists, overwrite it"))
 }
 
 pub fn arg_matcher() -> Command {
@@ -142,4 +500,5 @@ pub fn arg_matcher() -> Command {
         .subcommand(subcom_convert())
         .subcommand(subcom_validate())
         .subcommand(subcom_generate())
+        .subcommand(subcom_sanitize())
 }
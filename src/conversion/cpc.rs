@@ -0,0 +1,58 @@
+// SPDX-FileCopyrightText: 2022 - 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Maps OSHWA project categories to
+//! [CPC](https://www.cooperativepatentclassification.org/) patent classes.
+//!
+//! This mapping is defined here:
+//! <https://github.com/OPEN-NEXT/OKH-LOSH/blob/master/data_mapping/data-mapping-OSHWA.md#category-mapping>
+
+/// OSHWA categories that have no CPC equivalent
+pub const CATEGORIES_UNMAPPABLE: &[&str] = &[
+    "Arts",
+    "Education",
+    "Environmental",
+    "Manufacturing",
+    "Other",
+    "Science",
+    "Tool",
+];
+
+/// OSHWA (primary) categories and their CPC equivalent
+pub const CATEGORIES_PRIMARY_TO_CPC: &[(&str, &str)] = &[
+    ("3D Printing", "B33Y"),
+    ("Agriculture", "A01"),
+    ("Electronics", "H"),
+    ("Enclosure", "F16M"),
+    ("Home Connection", "H04W"),
+    ("IOT", "H04"),
+    ("Robotics", "B25J9/00"),
+    ("Sound", "H04R"),
+    ("Space", "B64G"),
+    ("Wearables", "H"),
+];
+
+/// What to do with a value found where a CPC patent class is expected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mapping<'a> {
+    /// It is a known OSHWA category, with this CPC equivalent.
+    Cpc(&'static str),
+    /// It is empty, or a known OSHWA category without a CPC equivalent.
+    Unmappable,
+    /// It is not a known OSHWA category,
+    /// so it is assumed to already be a CPC patent class.
+    AsIs(&'a str),
+}
+
+/// Maps an OSHWA category to a CPC patent class.
+pub fn from_oshwa_category(category: &str) -> Mapping<'_> {
+    let trimmed = category.trim();
+    if trimmed.is_empty() || CATEGORIES_UNMAPPABLE.contains(&trimmed) {
+        return Mapping::Unmappable;
+    }
+    CATEGORIES_PRIMARY_TO_CPC
+        .iter()
+        .find(|(oshwa, _)| *oshwa == trimmed)
+        .map_or(Mapping::AsIs(trimmed), |(_, cpc)| Mapping::Cpc(cpc))
+}
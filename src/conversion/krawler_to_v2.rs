@@ -0,0 +1,335 @@
+// SPDX-FileCopyrightText: 2022 - 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Converts the YAML files produced by the LOSH krawler
+//! into proper OKH LOSH manifests.
+//!
+//! The krawler YAML is structurally mostly OKH LOSH already,
+//! but file references come wrapped in meta-data (`{url: ..., path: ...}`),
+//! and some values need cleaning up.
+//! This is a port of the former `run/filter-yaml` Python script.
+
+use std::io::{Read, Write};
+
+use serde_json::{Map, Value};
+
+use crate::formats::ParseError;
+use crate::formats::v2;
+use crate::oxrl::{Odrl, Otrl};
+
+use super::Error;
+use super::cpc;
+use super::report::Report;
+
+pub const FORMAT_NAME: &str = "OKH-LOSH-Krawler";
+
+/// The krawler sets this as image URL for Thingiverse projects without an image.
+const BROKEN_THINGIVERSE_IMAGE_URL: &str = "https://cdn.thingiverse.com/";
+
+/// Properties that may come wrapped in meta-data
+const META_PROPS: &[&str] = &[
+    "bom",
+    "readme",
+    "image",
+    "source",
+    "export",
+    "manufacturing-instructions",
+    "user-manual",
+];
+
+/// Properties that are lists in OKH LOSH
+const LIST_PROPS: &[&str] = &[
+    "image",
+    "source",
+    "export",
+    "auxiliary",
+    "manufacturing-instructions",
+    "attestation",
+    "standard-compliance",
+    "standard",
+];
+
+/// Properties that are single values in OKH LOSH
+const SINGLE_PROPS: &[&str] = &["bom", "readme", "user-manual", "contribution-guide"];
+
+/// Extracts the plain value from a krawler meta-data wrapped value.
+fn plain(value: &Value, prop: &str) -> Result<Value, &'static str> {
+    match value {
+        Value::Object(meta) => meta.get("url").map_or_else(
+            || {
+                meta.get("path")
+                    .cloned()
+                    .ok_or("neither 'url' nor 'path' is given")
+            },
+            |url| {
+                if prop == "image" && url.as_str() == Some(BROKEN_THINGIVERSE_IMAGE_URL) {
+                    Err("broken Thingiverse placeholder image")
+                } else {
+                    Ok(url.clone())
+                }
+            },
+        ),
+        Value::Null => Err("empty"),
+        Value::Bool(_) | Value::Number(_) | Value::String(_) | Value::Array(_) => Ok(value.clone()),
+    }
+}
+
+/// Replaces meta-data wrapped values with the plain value.
+fn meta_to_plain(parent: &mut Map<String, Value>, prop: &str, prefix: &str, report: &mut Report) {
+    let Some(value) = parent.get_mut(prop) else {
+        return;
+    };
+    if let Value::Array(elems) = value {
+        *elems = elems
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, elem)| {
+                plain(elem, prop)
+                    .map_err(|reason| report.dropped(format!("{prefix}{prop}[{idx}]"), reason))
+                    .ok()
+            })
+            .collect();
+    } else {
+        match plain(value, prop) {
+            Ok(plain_value) => *value = plain_value,
+            Err(reason) => {
+                report.dropped(format!("{prefix}{prop}"), reason);
+                parent.remove(prop);
+            }
+        }
+    }
+}
+
+/// Makes sure list properties are lists,
+/// and single value properties are not.
+fn fix_cardinality(parent: &mut Map<String, Value>, prefix: &str, report: &mut Report) {
+    for prop in LIST_PROPS {
+        if let Some(value) = parent.get_mut(*prop)
+            && !value.is_array()
+        {
+            *value = Value::Array(vec![value.take()]);
+        }
+    }
+    for prop in SINGLE_PROPS {
+        if let Some(Value::Array(elems)) = parent.get_mut(*prop) {
+            for idx in 1..elems.len() {
+                report.dropped(
+                    format!("{prefix}{prop}[{idx}]"),
+                    "OKH LOSH supports only a single value",
+                );
+            }
+            let first = elems.drain(..).next();
+            if let Some(first_val) = first {
+                parent.insert((*prop).to_owned(), first_val);
+            } else {
+                parent.remove(*prop);
+            }
+        }
+    }
+}
+
+fn filter_module(module: &mut Map<String, Value>, prefix: &str, report: &mut Report) {
+    for prop in META_PROPS {
+        meta_to_plain(module, prop, prefix, report);
+    }
+    fix_cardinality(module, prefix, report);
+}
+
+fn clean_function(function: &str) -> String {
+    function
+        .replace('\r', "")
+        .replace("\n\\ ", "\n")
+        .replace("\\'", "'")
+        .replace("\\\"", "\"")
+}
+
+fn clean_odrl(odrl: &str) -> String {
+    odrl.replace("ODLR", "ODRL")
+        .replace("ODRL", "ODRL-")
+        .replace("--", "-")
+        .replace("Odrl", "ODRL-")
+        .replace("odrl", "ODRL-")
+        .replace("Star", "*")
+        .replace("star", "*")
+}
+
+fn clean_otrl(otrl: &str) -> String {
+    otrl.replace("OTLR", "OTRL")
+        .replace("OTRL", "OTRL-")
+        .replace("--", "-")
+        .replace("Otrl", "OTRL-")
+        .replace("ortl", "OTRL-")
+        .replace("otrl", "OTRL-")
+}
+
+/// Cleans up a readiness level value,
+/// removing it if it is still not valid afterwards.
+fn fix_oxrl<T: serde::de::DeserializeOwned>(
+    module: &mut Map<String, Value>,
+    prop: &str,
+    clean: fn(&str) -> String,
+    report: &mut Report,
+) {
+    let Some(raw) = module.get(prop).and_then(Value::as_str).map(clean) else {
+        return;
+    };
+    let cleaned = Value::String(raw.clone());
+    if serde_json::from_value::<T>(cleaned.clone()).is_ok() {
+        module.insert(prop.to_owned(), cleaned);
+    } else {
+        report.dropped(prop, format!("'{raw}' is not a valid readiness level"));
+        module.remove(prop);
+    }
+}
+
+fn fix_cpc_patent_class(module: &mut Map<String, Value>, report: &mut Report) {
+    let prop = "cpc-patent-class";
+    let Some(raw) = module
+        .get(prop)
+        .and_then(Value::as_str)
+        .map(ToOwned::to_owned)
+    else {
+        return;
+    };
+    match cpc::from_oshwa_category(&raw) {
+        cpc::Mapping::Cpc(cpc) => {
+            module.insert(prop.to_owned(), Value::String(cpc.to_owned()));
+        }
+        cpc::Mapping::Unmappable => {
+            report.dropped(prop, format!("'{raw}' has no CPC equivalent"));
+            module.remove(prop);
+        }
+        cpc::Mapping::AsIs(_) => (),
+    }
+}
+
+/// Reports the properties of a software or sub-part entry
+/// (and recursively, of its sub-parts)
+/// that do not make it into the manifest.
+/// `prefix` is the path of the entry, including the trailing '.'.
+fn report_nested_drops(
+    entry: &Map<String, Value>,
+    keys: &[&str],
+    prefix: &str,
+    report: &mut Report,
+) {
+    for (prop, value) in entry {
+        if value.is_null() {
+            continue;
+        }
+        if !keys.contains(&prop.as_str()) {
+            report.dropped(format!("{prefix}{prop}"), "not part of OKH LOSH");
+            continue;
+        }
+        if prop == "part"
+            && let Value::Array(parts) = value
+        {
+            report_part_drops(parts, &format!("{prefix}part"), report);
+        }
+    }
+}
+
+/// Reports the properties of sub-parts that do not make it into the manifest.
+fn report_part_drops(parts: &[Value], path: &str, report: &mut Report) {
+    for (idx, part) in parts.iter().enumerate() {
+        if let Value::Object(part_map) = part {
+            let prefix = format!("{path}[{idx}].");
+            report_nested_drops(part_map, v2::SUB_MOSH_KEYS, &prefix, report);
+        }
+    }
+}
+
+/// Reports which of the properties make it into the manifest.
+/// Of software entries and sub-parts, only the dropped ones are reported.
+fn report_props(module: &Map<String, Value>, report: &mut Report) {
+    for (prop, value) in module {
+        if value.is_null() {
+            continue;
+        }
+        let Some(key) = v2::KEYS.iter().find(|key| *key == prop) else {
+            report.dropped(prop.as_str(), "not part of OKH LOSH");
+            continue;
+        };
+        report.mapped(prop.as_str(), key);
+        match (prop.as_str(), value) {
+            ("software", Value::Array(softwares)) => {
+                for (idx, software) in softwares.iter().enumerate() {
+                    if let Value::Object(software_map) = software {
+                        let prefix = format!("software[{idx}].");
+                        report_nested_drops(software_map, v2::SOFTWARE_KEYS, &prefix, report);
+                    }
+                }
+            }
+            ("part", Value::Array(parts)) => report_part_drops(parts, "part", report),
+            _ => (),
+        }
+    }
+}
+
+/// Converts krawler YAML (already parsed) into an OKH LOSH manifest.
+///
+/// # Errors
+///
+/// If the data is not structured as expected,
+/// or misses properties required by OKH LOSH.
+pub fn convert(krawler: Value) -> Result<(v2::Okh, Report), Error> {
+    log::debug!("Converting krawler YAML to OKH v2 ...");
+    let mut report = Report::new(FORMAT_NAME, v2::FORMAT_NAME);
+    let Value::Object(mut module) = krawler else {
+        return Err(Error::InsufficientData {
+            msg: "the krawler YAML needs to contain a mapping at the top level",
+        });
+    };
+
+    filter_module(&mut module, "", &mut report);
+    if let Some(Value::Array(parts)) = module.get_mut("part") {
+        for (idx, part) in parts.iter_mut().enumerate() {
+            if let Value::Object(part_map) = part {
+                filter_module(part_map, &format!("part[{idx}]."), &mut report);
+            }
+        }
+    }
+
+    if let Some(Value::String(function)) = module.get_mut("function") {
+        *function = clean_function(function);
+    }
+    fix_oxrl::<Odrl>(
+        &mut module,
+        "documentation-readiness-level",
+        clean_odrl,
+        &mut report,
+    );
+    fix_oxrl::<Otrl>(
+        &mut module,
+        "technology-readiness-level",
+        clean_otrl,
+        &mut report,
+    );
+    fix_cpc_patent_class(&mut module, &mut report);
+
+    // Nulls are not representable in TOML
+    module.retain(|_, value| !value.is_null());
+    report_props(&module, &mut report);
+
+    let v2 = serde_json::from_value::<v2::Okh>(Value::Object(module)).map_err(ParseError::from)?;
+    Ok((v2, report))
+}
+
+/// Converts krawler YAML read from `input`,
+/// writing the OKH LOSH manifest to `output`.
+///
+/// # Errors
+///
+/// If reading, converting or writing fails.
+pub fn convert_io<R: Read, W: Write>(mut input: R, output: W) -> Result<Report, Error> {
+    log::debug!("Reading krawler YAML to string ...");
+    let mut yaml_str = String::new();
+    input
+        .read_to_string(&mut yaml_str)
+        .map_err(ParseError::from)?;
+    let krawler = serde_yaml::from_str::<Value>(&yaml_str).map_err(ParseError::from)?;
+    let (v2, report) = convert(krawler)?;
+    v2.to_toml_writer(output)?;
+    Ok(report)
+}
@@ -0,0 +1,164 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! User-configurable rules for the parts of the OKH v1 to OKH LOSH conversion
+//! that can not be mapped one-to-one,
+//! and thus require some kind of decision or heuristic.
+//!
+//! The [`Default`] rule set represents the built-in behavior.
+//! A rules file only needs to contain the values that should differ from it,
+//! for example:
+//!
+//! ```toml
+//! [function]
+//! separator = "\n\n"
+//!
+//! [license]
+//! order = ["documentation", "hardware"]
+//!
+//! [otrl.development-stage]
+//! prototype = "OTRL-4"
+//! production = "OTRL-5"
+//! ```
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use serde::Deserialize;
+
+use crate::formats::ParseError;
+use crate::oxrl::{Odrl, Otrl};
+
+/// One of the licenses an OKH v1 manifest may specify.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LicenseSource {
+    Hardware,
+    Documentation,
+    Software,
+}
+
+/// How the `function` is assembled from
+/// `description`, `intended-use` and `health-safety-notice`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct FunctionRules {
+    /// Put between the (non-empty) merged values
+    pub separator: String,
+}
+
+impl Default for FunctionRules {
+    fn default() -> Self {
+        Self {
+            separator: "\n".to_string(),
+        }
+    }
+}
+
+/// How the (single) OKH LOSH license expression is combined
+/// from the OKH v1 licenses.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct LicenseRules {
+    /// The licenses of these that are set in the source
+    /// are combined with `AND`, in this order;
+    /// all others are dropped.
+    pub order: Vec<LicenseSource>,
+}
+
+impl Default for LicenseRules {
+    fn default() -> Self {
+        Self {
+            order: vec![
+                LicenseSource::Hardware,
+                LicenseSource::Documentation,
+                LicenseSource::Software,
+            ],
+        }
+    }
+}
+
+/// How the `documentation-readiness-level` is guessed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct OdrlRules {
+    /// Used if `made-independently` is `true`
+    pub made_independently: Odrl,
+    /// Used in all other cases
+    pub otherwise: Odrl,
+}
+
+impl Default for OdrlRules {
+    fn default() -> Self {
+        Self {
+            made_independently: Odrl::Full,
+            otherwise: Odrl::Started,
+        }
+    }
+}
+
+/// How the `technology-readiness-level` is guessed.
+/// If more than one of the rules apply,
+/// the highest level is used.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct OtrlRules {
+    /// Used if `made` is `true`
+    pub made: Otrl,
+    /// Used if `made-independently` is `true`
+    pub made_independently: Otrl,
+    /// Maps values of `development-stage` (compared case-insensitively)
+    /// to the level to use
+    pub development_stage: BTreeMap<String, Otrl>,
+    /// Used if none of the above apply
+    pub otherwise: Otrl,
+}
+
+impl Default for OtrlRules {
+    fn default() -> Self {
+        Self {
+            made: Otrl::Development,
+            made_independently: Otrl::Development,
+            development_stage: BTreeMap::from([("prototype".to_string(), Otrl::Development)]),
+            otherwise: Otrl::Ideation,
+        }
+    }
+}
+
+impl OtrlRules {
+    /// Looks up the level for a `development-stage` value.
+    pub fn for_development_stage(&self, development_stage: &str) -> Option<Otrl> {
+        let stage = development_stage.trim().to_lowercase();
+        self.development_stage
+            .iter()
+            .find(|(key, _)| key.to_lowercase() == stage)
+            .map(|(_, otrl)| *otrl)
+    }
+}
+
+/// All the rules for the OKH v1 to OKH LOSH conversion.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Rules {
+    pub function: FunctionRules,
+    pub license: LicenseRules,
+    pub odrl: OdrlRules,
+    pub otrl: OtrlRules,
+}
+
+impl Rules {
+    pub fn from_toml(toml_str: &str) -> Result<Self, ParseError> {
+        log::debug!("Parsing mapping rules TOML ...");
+        Ok(toml::from_str::<Self>(toml_str)?)
+    }
+
+    pub fn from_toml_file<OP>(toml_file: OP) -> Result<Self, ParseError>
+    where
+        OP: AsRef<Path>,
+    {
+        log::debug!("Reading mapping rules TOML file to string ...");
+        let toml_str = fs::read_to_string(toml_file)?;
+
+        Self::from_toml(&toml_str)
+    }
+}
@@ -2,22 +2,162 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+pub mod cpc;
+pub mod krawler_to_v2;
+pub mod mapping;
+pub mod oshwa_to_v2;
+pub mod rdf_to_v2;
+pub mod report;
+pub mod timestamp;
 pub mod v1_to_v2;
+pub mod v2_json;
+pub mod v2_to_citation;
+pub mod v2_to_rdf;
+pub mod v2_to_v1;
 
-#[derive(thiserror::Error, Debug)]
+use std::path::PathBuf;
+
+use crate::formats::{ParseError, SerError, v2};
+use crate::license::{self, alias::Confidence};
+
+use mapping::{LicenseRules, LicenseSource};
+use report::Treatment;
+
+/// Settings that influence how conversions are done.
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// A local directory mirroring remote manifests,
+    /// laid out as `<host>/<url-path>`;
+    /// used to find the manifests of sub-parts.
+    pub manifest_cache: Option<PathBuf>,
+    /// Decides the parts of the conversion
+    /// that can not be mapped one-to-one
+    pub rules: mapping::Rules,
+    /// Do not use the git commit time of the input file as timestamp,
+    /// to get reproducible results
+    pub no_git_timestamp: bool,
+    /// Parse OKH v1 manifests leniently,
+    /// leaving out the properties that can not be read,
+    /// instead of failing
+    pub lenient: bool,
+}
+
+#[derive(thiserror::Error, Debug, strum_macros::IntoStaticStr)]
 pub enum Error {
     #[error("The source meta-data specifies no license.")]
     NoLicense,
 
+    #[error("The combined license '{expr}' is not a valid SPDX expression")]
+    InvalidLicense {
+        expr: String,
+        source: license::Error,
+    },
+
     #[error("Insufficient data found in source - {msg}")]
     InsufficientData { msg: &'static str },
 
+    #[error("Invalid RDF data found in source - {msg}")]
+    InvalidRdf { msg: String },
+
     #[error("Some problem with git, for example, no repo present, or no branch/tag checked out")]
     GitProblem(#[from] git2::Error),
 
     #[error("Failed to parse the input format")]
-    Parse(#[from] crate::formats::ParseError),
+    Parse(#[from] ParseError),
 
     #[error("Failed to serialize into the output format")]
-    Serialize(#[from] crate::formats::SerError),
+    Serialize(#[from] SerError),
+
+    #[error("The converted manifest is invalid")]
+    Validation(#[from] crate::validation::Error),
+}
+
+/// Maps a source license value to an SPDX expression,
+/// and reports how that was done, including the confidence of the mapping.
+fn map_license(source: &str, license_val: &str, report: &mut report::Report) -> String {
+    let mapping = license::alias::to_spdx(license_val);
+    let note = (mapping.expression != license_val).then(|| {
+        format!(
+            "'{license_val}' is not a valid SPDX expression; mapped to '{}' (confidence: {})",
+            mapping.expression, mapping.confidence
+        )
+    });
+    let treatment = if mapping.confidence > Confidence::Low {
+        Treatment::Mapped
+    } else {
+        Treatment::Guessed
+    };
+    report.push(source, treatment, Some("license"), note);
+    mapping.expression
+}
+
+/// Combines the licenses of the different aspects of a project
+/// into a single SPDX expression (joined with `AND`),
+/// in the order given by the rules.
+/// `candidates` are the aspects, the names of their source properties
+/// and their (possibly missing) license values.
+/// Returns the expression, and which license applies to which aspect,
+/// if it was combined from different licenses,
+/// or if one of them is the software license,
+/// which OKH v1 keeps apart from the others.
+/// Empty license values are skipped.
+fn combine_licenses(
+    candidates: &[(LicenseSource, &str, Option<&str>)],
+    rules: &LicenseRules,
+    report: &mut report::Report,
+) -> Result<(String, Option<v2::LicenseAspects>), Error> {
+    let mut aspects = v2::LicenseAspects::default();
+    let mut expressions: Vec<String> = Vec::new();
+    for src in &rules.order {
+        let Some(&(_, name, Some(lcse_val))) =
+            candidates.iter().find(|(cand_src, _, _)| cand_src == src)
+        else {
+            continue;
+        };
+        if lcse_val.trim().is_empty() {
+            report.dropped(name, "empty");
+            continue;
+        }
+        let expression = map_license(name, lcse_val, report);
+        if !expressions.contains(&expression) {
+            expressions.push(expression.clone());
+        }
+        let aspect = match src {
+            LicenseSource::Hardware => &mut aspects.hardware,
+            LicenseSource::Documentation => &mut aspects.documentation,
+            LicenseSource::Software => &mut aspects.software,
+        };
+        *aspect = Some(expression);
+    }
+    for (src, name, lcse) in candidates {
+        if lcse.is_some() && !rules.order.contains(src) {
+            report.dropped(
+                *name,
+                "not listed in the license order of the mapping rules",
+            );
+        }
+    }
+    let combined = match expressions.as_slice() {
+        [] => return Err(Error::NoLicense),
+        [single] => single.clone(),
+        multiple => multiple
+            .iter()
+            .map(|expr| {
+                if expr.contains(char::is_whitespace) {
+                    format!("({expr})")
+                } else {
+                    expr.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" AND "),
+    };
+    license::validate_spdx_expr(&combined, false).map_err(|source| Error::InvalidLicense {
+        expr: combined.clone(),
+        source,
+    })?;
+    Ok((
+        combined,
+        (expressions.len() > 1 || aspects.software.is_some()).then_some(aspects),
+    ))
 }
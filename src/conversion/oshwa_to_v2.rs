@@ -0,0 +1,225 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Imports OSHWA certification records as OKH LOSH manifests.
+
+use std::io::{Read, Write};
+
+use url::Url;
+
+use crate::formats::oshwa::{self, Certification};
+use crate::formats::v2;
+
+use super::Error;
+use super::Options;
+use super::cpc;
+use super::mapping::{LicenseRules, LicenseSource};
+use super::report::{Report, Treatment};
+
+fn non_empty(value: Option<&String>) -> Option<&str> {
+    value.map(|val| val.trim()).filter(|val| !val.is_empty())
+}
+
+fn repo(cert: &Certification, report: &mut Report) -> Result<Url, Error> {
+    let (source, repo_str) = if let Some(website) = non_empty(cert.project_website.as_ref()) {
+        ("projectWebsite", website)
+    } else if let Some(docu) = non_empty(cert.documentation_url.as_ref()) {
+        ("documentationUrl", docu)
+    } else {
+        return Err(Error::InsufficientData {
+            msg: "one of 'projectWebsite' and 'documentationUrl' is required to convert to OKH LOSH",
+        });
+    };
+    let repo = Url::parse(repo_str).map_err(|_err| Error::InsufficientData {
+        msg: "the project URL is not a valid URL",
+    })?;
+    report.mapped(source, "repo");
+    Ok(repo)
+}
+
+fn version(cert: &Certification, report: &mut Report) -> Result<String, Error> {
+    let version = non_empty(cert.project_version.as_ref()).ok_or(Error::InsufficientData {
+        msg: "'projectVersion' is required for OKH LOSH",
+    })?;
+    report.mapped("projectVersion", "version");
+    Ok(version.to_owned())
+}
+
+fn license(
+    cert: &Certification,
+    rules: &LicenseRules,
+    report: &mut Report,
+) -> Result<(String, Option<v2::LicenseAspects>), Error> {
+    let candidates = [
+        (
+            LicenseSource::Hardware,
+            "hardwareLicense",
+            &cert.hardware_license,
+        ),
+        (
+            LicenseSource::Documentation,
+            "documentationLicense",
+            &cert.documentation_license,
+        ),
+        (
+            LicenseSource::Software,
+            "softwareLicense",
+            &cert.software_license,
+        ),
+    ]
+    .map(|(src, name, lcse)| {
+        let lcse_val = lcse.as_deref().filter(|lcse_val| {
+            let is_license = Certification::is_license(lcse_val);
+            if !is_license {
+                report.dropped(name, format!("'{lcse_val}' does not denote a license"));
+            }
+            is_license
+        });
+        (src, name, lcse_val.map(str::trim))
+    });
+    super::combine_licenses(&candidates, rules, report)
+}
+
+fn licensor(cert: &Certification, report: &mut Report) -> String {
+    report.merged("responsibleParty", "licensor");
+    let mut licensor = cert.responsible_party.trim().to_owned();
+    if let Some(contact) = non_empty(cert.public_contact.as_ref()) {
+        report.merged("publicContact", "licensor");
+        if !licensor.is_empty() {
+            licensor.push(' ');
+        }
+        licensor.push('<');
+        licensor.push_str(contact);
+        licensor.push('>');
+    }
+    licensor
+}
+
+/// Uses the first of the OSHWA categories that maps to a CPC patent class.
+fn cpc_patent_class(cert: &Certification, report: &mut Report) -> Option<String> {
+    let categories = cert
+        .primary_type
+        .iter()
+        .map(|cat| ("primaryType".to_owned(), cat))
+        .chain(
+            cert.additional_type
+                .iter()
+                .enumerate()
+                .map(|(idx, cat)| (format!("additionalType[{idx}]"), cat)),
+        );
+    let mut chosen = None;
+    for (source, category) in categories {
+        if let cpc::Mapping::Cpc(cpc) = cpc::from_oshwa_category(category) {
+            if chosen.is_none() {
+                report.push(
+                    source,
+                    Treatment::Mapped,
+                    Some("cpc-patent-class"),
+                    Some(format!("OSHWA category '{category}' is CPC '{cpc}'")),
+                );
+                chosen = Some(cpc.to_owned());
+            } else {
+                report.dropped(source, "OKH LOSH supports only a single CPC patent class");
+            }
+        } else {
+            report.dropped(source, format!("'{category}' has no CPC equivalent"));
+        }
+    }
+    chosen
+}
+
+fn report_unmapped(cert: &Certification, report: &mut Report) {
+    if cert.country.is_some() {
+        report.dropped("country", "not supported by OKH LOSH");
+    }
+    if !cert.project_keywords.is_empty() {
+        report.dropped("projectKeywords", "not supported by OKH LOSH");
+    }
+    if cert.project_website.is_some() && cert.documentation_url.is_some() {
+        report.dropped("documentationUrl", "not supported by OKH LOSH");
+    }
+    if cert.date.is_some() {
+        report.dropped("certificationDate", "not supported by OKH LOSH");
+    }
+}
+
+/// Converts an OSHWA certification record into an OKH LOSH manifest.
+///
+/// # Errors
+///
+/// If the record misses data required by OKH LOSH.
+pub fn convert(cert: &Certification, options: &Options) -> Result<(v2::Okh, Report), Error> {
+    log::debug!("Converting OSHWA certification to OKH v2 ...");
+    let mut report = Report::new(oshwa::FORMAT_NAME, v2::FORMAT_NAME);
+
+    report.mapped("projectName", "name");
+    let attestation =
+        Url::parse(&cert.certification_page_url()).map_err(|_err| Error::InsufficientData {
+            msg: "'oshwaUid' does not form a valid certification page URL",
+        })?;
+    report.guessed(
+        "oshwaUid",
+        "attestation",
+        "the URL of the OSHWA certification page",
+    );
+    let function = non_empty(cert.project_description.as_ref()).map(ToOwned::to_owned);
+    if function.is_some() {
+        report.mapped("projectDescription", "function");
+    }
+    report_unmapped(cert, &mut report);
+
+    let (license, license_aspects) = license(cert, &options.rules.license, &mut report)?;
+    let v2 = v2::Okh {
+        okhv: v2::OKHV.to_owned(),
+        name: cert.project_name.trim().to_owned(),
+        repo: repo(cert, &mut report)?,
+        version: version(cert, &mut report)?,
+        license,
+        licensor: licensor(cert, &mut report),
+        upload_method: None,
+        organisation: None,
+        readme: None,
+        contribution_guide: None,
+        image: vec![],
+        documentation_language: None,
+        attestation: vec![attestation],
+        standard_compliance: vec![],
+        cpc_patent_class: cpc_patent_class(cert, &mut report),
+        tsdc: None,
+        bom: None,
+        release: None,
+        timestamp: None,
+        fork_of: None,
+        function,
+        documentation_readiness_level: None,
+        technology_readiness_level: None,
+        user_manual: None,
+        manufacturing_instructions: vec![],
+        software: vec![],
+        standard: vec![],
+        source: vec![],
+        export: vec![],
+        auxiliary: vec![],
+        license_aspects,
+        part: vec![],
+    };
+    Ok((v2, report))
+}
+
+/// Converts an OSHWA certification record read from `input`,
+/// writing the OKH LOSH manifest to `output`.
+///
+/// # Errors
+///
+/// If reading, converting or writing fails.
+pub fn convert_io<R: Read, W: Write>(
+    input: R,
+    output: W,
+    options: &Options,
+) -> Result<Report, Error> {
+    let cert = Certification::from_json_reader(input)?;
+    let (v2, report) = convert(&cert, options)?;
+    v2.to_toml_writer(output)?;
+    Ok(report)
+}
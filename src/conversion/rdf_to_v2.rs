@@ -0,0 +1,382 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+use oxrdf::vocab::{rdf, rdfs};
+use oxrdf::{NamedNode, NamedOrBlankNode, Term};
+use oxttl::TurtleParser;
+use relative_path::RelativePathBuf;
+use serde::de::DeserializeOwned;
+use url::Url;
+
+use crate::formats::rdf::{self as okh_rdf, NS_ODRL, NS_OKH, NS_OTRL, class, okh_node, prop};
+use crate::formats::{ParseError, SerError, v2};
+use crate::validation;
+
+use super::Error;
+use super::report::Report;
+
+fn invalid<M: Into<String>>(msg: M) -> Error {
+    Error::InvalidRdf { msg: msg.into() }
+}
+
+/// Returns the lexical value of a literal or IRI.
+fn term_str(term: &Term) -> Option<&str> {
+    if let Term::Literal(literal) = term {
+        Some(literal.value())
+    } else if let Term::NamedNode(node) = term {
+        Some(node.as_str())
+    } else {
+        None
+    }
+}
+
+/// Returns the term as a subject, if it is a named or blank node.
+fn term_node(term: &Term) -> Option<NamedOrBlankNode> {
+    if let Term::NamedNode(node) = term {
+        Some(node.clone().into())
+    } else if let Term::BlankNode(node) = term {
+        Some(node.clone().into())
+    } else {
+        None
+    }
+}
+
+/// Returns the repo relative path of a file reference,
+/// which may be given as an IRI within the repo (see [`okh_rdf::file_iri`]),
+/// or as a plain literal (e.g. `"README.md"`).
+fn term_path(term: &Term, repo: &Url, local_name: &str) -> Option<Result<RelativePathBuf, Error>> {
+    if let Term::NamedNode(node) = term {
+        Some(okh_rdf::file_path(repo, node.as_str()).ok_or_else(|| {
+            invalid(format!(
+                "'okh:{local_name}' - <{}> is not a file within the repo <{repo}>",
+                node.as_str()
+            ))
+        }))
+    } else if let Term::Literal(literal) = term {
+        Some(Ok(RelativePathBuf::from(literal.value())))
+    } else {
+        None
+    }
+}
+
+/// Parses an ODRL or OTRL value,
+/// which may be given as an individual IRI (e.g. `otrl:OTRL-4`),
+/// or as a plain literal (e.g. `"OTRL-4"`).
+fn oxrl<T: DeserializeOwned>(namespace: &str, value: &str) -> Result<T, Error> {
+    let name = value.strip_prefix(namespace).unwrap_or(value);
+    serde_json::from_value(serde_json::Value::String(name.to_owned()))
+        .map_err(|_err| invalid(format!("'{value}' is not a valid readiness level")))
+}
+
+/// All the triples of a Turtle document,
+/// indexed by subject.
+#[derive(Default)]
+struct Graph {
+    props: HashMap<NamedOrBlankNode, Vec<(NamedNode, Term)>>,
+}
+
+impl Graph {
+    fn parse(turtle: &[u8]) -> Result<Self, ParseError> {
+        log::debug!("Parsing Turtle ...");
+        let mut graph = Self::default();
+        for parsed in TurtleParser::new().for_slice(turtle) {
+            let triple = parsed.map_err(oxttl::TurtleParseError::from)?;
+            graph
+                .props
+                .entry(triple.subject)
+                .or_default()
+                .push((triple.predicate, triple.object));
+        }
+        Ok(graph)
+    }
+
+    /// Returns all the values of a property of a subject,
+    /// in document order.
+    fn objects<'a>(
+        &'a self,
+        subject: &NamedOrBlankNode,
+        predicate: &'a str,
+    ) -> impl Iterator<Item = &'a Term> {
+        self.props
+            .get(subject)
+            .into_iter()
+            .flatten()
+            .filter(move |(pred, _)| pred.as_str() == predicate)
+            .map(|(_, object)| object)
+    }
+
+    fn strs(&self, subject: &NamedOrBlankNode, predicate: &str) -> Vec<String> {
+        self.objects(subject, predicate)
+            .filter_map(term_str)
+            .map(ToOwned::to_owned)
+            .collect()
+    }
+
+    fn str(&self, subject: &NamedOrBlankNode, predicate: &str) -> Option<String> {
+        let mut values = self.strs(subject, predicate);
+        if values.len() > 1 {
+            log::warn!("Using only the first of multiple values for <{predicate}> of {subject}");
+        }
+        (!values.is_empty()).then(|| values.swap_remove(0))
+    }
+
+    fn okh_strs(&self, subject: &NamedOrBlankNode, local_name: &str) -> Vec<String> {
+        self.strs(subject, okh_node(local_name).as_str())
+    }
+
+    fn okh_str(&self, subject: &NamedOrBlankNode, local_name: &str) -> Option<String> {
+        self.str(subject, okh_node(local_name).as_str())
+    }
+
+    fn required_str(&self, subject: &NamedOrBlankNode, local_name: &str) -> Result<String, Error> {
+        self.okh_str(subject, local_name).ok_or_else(|| {
+            invalid(format!(
+                "required property 'okh:{local_name}' is missing on {subject}"
+            ))
+        })
+    }
+
+    fn urls(&self, subject: &NamedOrBlankNode, local_name: &str) -> Result<Vec<Url>, Error> {
+        self.okh_strs(subject, local_name)
+            .iter()
+            .map(|url| {
+                Url::parse(url)
+                    .map_err(|err| invalid(format!("'okh:{local_name}' - '{url}': {err}")))
+            })
+            .collect()
+    }
+
+    fn url(&self, subject: &NamedOrBlankNode, local_name: &str) -> Result<Option<Url>, Error> {
+        Ok(self.urls(subject, local_name)?.into_iter().next())
+    }
+
+    fn paths(
+        &self,
+        subject: &NamedOrBlankNode,
+        local_name: &str,
+        repo: &Url,
+    ) -> Result<Vec<RelativePathBuf>, Error> {
+        self.objects(subject, okh_node(local_name).as_str())
+            .filter_map(|term| term_path(term, repo, local_name))
+            .collect()
+    }
+
+    fn path(
+        &self,
+        subject: &NamedOrBlankNode,
+        local_name: &str,
+        repo: &Url,
+    ) -> Result<Option<RelativePathBuf>, Error> {
+        Ok(self.paths(subject, local_name, repo)?.into_iter().next())
+    }
+
+    /// Returns the nodes linked to from a subject with the given property.
+    fn nodes(&self, subject: &NamedOrBlankNode, local_name: &str) -> Vec<NamedOrBlankNode> {
+        self.objects(subject, okh_node(local_name).as_str())
+            .filter_map(term_node)
+            .collect()
+    }
+
+    fn has_type(&self, subject: &NamedOrBlankNode, class_name: &str) -> bool {
+        let class_iri = okh_node(class_name);
+        self.objects(subject, rdf::TYPE.as_str())
+            .any(|object| term_str(object) == Some(class_iri.as_str()))
+    }
+
+    /// Finds the (top-level) module described in the document.
+    /// That is the module with an `okh:okhv`,
+    /// or if there is none, the one that is no part of an other one.
+    fn root_module(&self) -> Result<NamedOrBlankNode, Error> {
+        let modules: Vec<&NamedOrBlankNode> = self
+            .props
+            .keys()
+            .filter(|subject| self.has_type(subject, class::MODULE))
+            .collect();
+        let with_okhv: Vec<&NamedOrBlankNode> = modules
+            .iter()
+            .copied()
+            .filter(|module| self.okh_str(module, prop::OKHV).is_some())
+            .collect();
+        let candidates = if with_okhv.is_empty() {
+            let parts: Vec<NamedOrBlankNode> = modules
+                .iter()
+                .flat_map(|module| self.nodes(module, prop::PART))
+                .collect();
+            modules
+                .into_iter()
+                .filter(|module| !parts.contains(module))
+                .collect()
+        } else {
+            with_okhv
+        };
+        match candidates.as_slice() {
+            [module] => Ok((*module).clone()),
+            [] => Err(invalid(format!(
+                "no (top-level) 'okh:{}' found",
+                class::MODULE
+            ))),
+            _ => Err(invalid(format!(
+                "found {} top-level modules, but only one is supported per file",
+                candidates.len()
+            ))),
+        }
+    }
+
+    fn software(&self, module: &NamedOrBlankNode) -> Result<Vec<v2::Software>, Error> {
+        self.nodes(module, prop::SOFTWARE)
+            .iter()
+            .map(|sw| {
+                Ok(v2::Software {
+                    label: self.str(sw, rdfs::LABEL.as_str()),
+                    release: self.url(sw, prop::RELEASE)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Reads the parts of a (sub-)module, recursively.
+    fn parts(
+        &self,
+        module: &NamedOrBlankNode,
+        repo: &Url,
+        visited: &[NamedOrBlankNode],
+    ) -> Result<Vec<v2::SubMosh>, Error> {
+        let mut parts = vec![];
+        for part in self.nodes(module, prop::PART) {
+            if visited.contains(&part) {
+                return Err(invalid(format!("cyclic part reference to {part}")));
+            }
+            let mut sub_visited = visited.to_vec();
+            sub_visited.push(part.clone());
+            parts.push(v2::SubMosh {
+                name: self.okh_str(&part, prop::NAME),
+                image: self.paths(&part, prop::IMAGE, repo)?,
+                tsdc: self.okh_str(&part, prop::TSDC),
+                source: self.paths(&part, prop::SOURCE, repo)?,
+                export: self.paths(&part, prop::EXPORT, repo)?,
+                auxiliary: self.paths(&part, prop::AUXILIARY, repo)?,
+                part: self.parts(&part, repo, &sub_visited)?,
+            });
+        }
+        Ok(parts)
+    }
+}
+
+/// Reports what happens to each property of the module.
+fn report(graph: &Graph, module: &NamedOrBlankNode) -> Report {
+    let mut report = Report::new(okh_rdf::FORMAT_NAME, v2::FORMAT_NAME);
+    let mut seen = vec![];
+    for (predicate, _) in graph.props.get(module).into_iter().flatten() {
+        if seen.contains(&predicate) || *predicate == rdf::TYPE {
+            continue;
+        }
+        seen.push(predicate);
+        let known = predicate
+            .as_str()
+            .strip_prefix(NS_OKH)
+            .and_then(|local_name| {
+                okh_rdf::PROPS
+                    .iter()
+                    .find(|(prop_name, _)| *prop_name == local_name)
+            });
+        if let Some((local_name, target)) = known {
+            report.mapped(format!("okh:{local_name}"), target);
+        } else {
+            report.dropped(predicate.as_str(), "not part of OKH LOSH");
+        }
+    }
+    report
+}
+
+/// Reconstructs an OKH LOSH manifest from RDF,
+/// serialized as Turtle, using the OKH LOSH ontology.
+///
+/// # Errors
+///
+/// If the input is not valid Turtle,
+/// or does not describe exactly one OKH LOSH module,
+/// or lacks required properties.
+pub fn convert(turtle: &[u8]) -> Result<(v2::Okh, Report), Error> {
+    log::debug!("Converting RDF to OKH v2 ...");
+    let graph = Graph::parse(turtle)?;
+    let module = graph.root_module()?;
+    let report = report(&graph, &module);
+
+    let documentation_readiness_level = graph
+        .okh_str(&module, prop::ODRL)
+        .map(|odrl| oxrl(NS_ODRL, &odrl))
+        .transpose()?;
+    let technology_readiness_level = graph
+        .okh_str(&module, prop::OTRL)
+        .map(|otrl| oxrl(NS_OTRL, &otrl))
+        .transpose()?;
+    let repo_str = graph.required_str(&module, prop::REPO)?;
+    let repo = Url::parse(&repo_str)
+        .map_err(|err| invalid(format!("'okh:{}' - '{repo_str}': {err}", prop::REPO)))?;
+
+    let v2 = v2::Okh {
+        okhv: graph
+            .okh_str(&module, prop::OKHV)
+            .unwrap_or_else(|| v2::OKHV.to_owned()),
+        name: graph.required_str(&module, prop::NAME)?,
+        repo: repo.clone(),
+        version: graph.required_str(&module, prop::VERSION)?,
+        license: graph.required_str(&module, prop::LICENSE)?,
+        licensor: graph.required_str(&module, prop::LICENSOR)?,
+        upload_method: graph.okh_str(&module, prop::UPLOAD_METHOD),
+        organisation: graph.okh_str(&module, prop::ORGANISATION),
+        readme: graph.path(&module, prop::README, &repo)?,
+        contribution_guide: graph.path(&module, prop::CONTRIBUTION_GUIDE, &repo)?,
+        image: graph.paths(&module, prop::IMAGE, &repo)?,
+        documentation_language: graph.okh_str(&module, prop::DOCUMENTATION_LANGUAGE),
+        attestation: graph.urls(&module, prop::ATTESTATION)?,
+        standard_compliance: graph.okh_strs(&module, prop::STANDARD_COMPLIANCE),
+        cpc_patent_class: graph.okh_str(&module, prop::CPC_PATENT_CLASS),
+        tsdc: graph.okh_str(&module, prop::TSDC),
+        bom: graph.path(&module, prop::BOM, &repo)?,
+        release: graph.url(&module, prop::RELEASE)?,
+        timestamp: graph.okh_str(&module, prop::TIMESTAMP),
+        fork_of: graph.url(&module, prop::FORK_OF)?,
+        function: graph.okh_str(&module, prop::FUNCTION),
+        documentation_readiness_level,
+        technology_readiness_level,
+        user_manual: graph.path(&module, prop::USER_MANUAL, &repo)?,
+        manufacturing_instructions: graph.paths(
+            &module,
+            prop::MANUFACTURING_INSTRUCTIONS,
+            &repo,
+        )?,
+        software: graph.software(&module)?,
+        standard: graph.okh_strs(&module, prop::STANDARD),
+        source: graph.paths(&module, prop::SOURCE, &repo)?,
+        export: graph.paths(&module, prop::EXPORT, &repo)?,
+        auxiliary: graph.paths(&module, prop::AUXILIARY, &repo)?,
+        // NOTE This is not part of the ontology, see `v2_to_rdf`.
+        license_aspects: None,
+        part: graph.parts(&module, &repo, std::slice::from_ref(&module))?,
+    };
+    Ok((v2, report))
+}
+
+/// Converts Turtle read from `input` to an OKH LOSH manifest,
+/// validates it, and writes it to `output`.
+///
+/// # Errors
+///
+/// If reading, converting or writing fails,
+/// or if the resulting manifest is invalid.
+pub fn convert_io<R: Read, W: Write>(mut input: R, mut output: W) -> Result<Report, Error> {
+    let mut turtle = Vec::new();
+    input.read_to_end(&mut turtle).map_err(ParseError::from)?;
+    let (v2, report) = convert(&turtle)?;
+    let toml = v2.to_toml()?;
+    validation::okh_losh_toml_str(&toml)?;
+    output.write_all(toml.as_bytes()).map_err(SerError::from)?;
+    Ok(report)
+}
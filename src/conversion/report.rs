@@ -0,0 +1,171 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::{fmt, fs, path::Path};
+
+use serde::Serialize;
+
+use crate::formats::SerError;
+
+/// The file extension used for conversion reports,
+/// which are written next to the converted manifest.
+pub const FILE_EXT: &str = "conv-report.json";
+
+/// What happened to a single source property during a conversion.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Treatment {
+    /// The value was transferred (more or less) as is.
+    Mapped,
+    /// The value was combined with other source values
+    /// into a single target property.
+    Merged,
+    /// The target value was not available in the source,
+    /// and was derived from this source value by some heuristic.
+    Guessed,
+    /// The value could not be represented in the target format.
+    Dropped,
+}
+
+impl fmt::Display for Treatment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Mapped => "mapped",
+            Self::Merged => "merged",
+            Self::Guessed => "guessed",
+            Self::Dropped => "dropped",
+        })
+    }
+}
+
+/// The fate of a single source property.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Field {
+    /// The path of the property in the source manifest,
+    /// for example `operating-instructions[1]`.
+    pub source: String,
+    pub treatment: Treatment,
+    /// The name of the property in the target manifest,
+    /// if the value ended up anywhere.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<8} {}", self.treatment, self.source)?;
+        if let Some(target) = self.target {
+            write!(f, " -> {target}")?;
+        }
+        if let Some(note) = &self.note {
+            write!(f, " ({note})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Lists what happened to each property of the source manifest
+/// during a conversion,
+/// so lossy conversions can be reviewed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Report {
+    /// The format of the source manifest
+    pub from: &'static str,
+    /// The format of the target manifest
+    pub to: &'static str,
+    pub fields: Vec<Field>,
+}
+
+impl Report {
+    pub const fn new(from: &'static str, to: &'static str) -> Self {
+        Self {
+            from,
+            to,
+            fields: vec![],
+        }
+    }
+
+    pub fn push<S: Into<String>>(
+        &mut self,
+        source: S,
+        treatment: Treatment,
+        target: Option<&'static str>,
+        note: Option<String>,
+    ) {
+        self.fields.push(Field {
+            source: source.into(),
+            treatment,
+            target,
+            note,
+        });
+    }
+
+    pub fn mapped<S: Into<String>>(&mut self, source: S, target: &'static str) {
+        self.push(source, Treatment::Mapped, Some(target), None);
+    }
+
+    pub fn merged<S: Into<String>>(&mut self, source: S, target: &'static str) {
+        self.push(source, Treatment::Merged, Some(target), None);
+    }
+
+    pub fn guessed<S: Into<String>, N: Into<String>>(
+        &mut self,
+        source: S,
+        target: &'static str,
+        note: N,
+    ) {
+        self.push(source, Treatment::Guessed, Some(target), Some(note.into()));
+    }
+
+    pub fn dropped<S: Into<String>, N: Into<String>>(&mut self, source: S, note: N) {
+        self.push(source, Treatment::Dropped, None, Some(note.into()));
+    }
+
+    /// Adds all the fields of an other report,
+    /// for example the one of a sub-part,
+    /// prefixing their source paths.
+    pub fn extend_prefixed(&mut self, prefix: &str, other: Self) {
+        self.fields
+            .extend(other.fields.into_iter().map(|field| Field {
+                source: format!("{prefix}.{}", field.source),
+                ..field
+            }));
+    }
+
+    /// Whether any source value could not be represented in the target.
+    pub fn is_lossy(&self) -> bool {
+        self.fields
+            .iter()
+            .any(|field| field.treatment == Treatment::Dropped)
+    }
+
+    pub fn to_json(&self) -> Result<String, SerError> {
+        log::debug!("Serializing conversion report to JSON ...");
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn to_json_file<OP>(&self, json_file: OP) -> Result<(), SerError>
+    where
+        OP: AsRef<Path>,
+    {
+        let serialized = self.to_json()?;
+        log::debug!("Writing conversion report to JSON file ...");
+        fs::write(json_file, serialized)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Conversion report ({} -> {}):", self.from, self.to)?;
+        for field in &self.fields {
+            writeln!(f, "    {field}")?;
+        }
+        Ok(())
+    }
+}
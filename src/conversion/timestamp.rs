@@ -0,0 +1,86 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Figures out and normalizes manifest timestamps.
+//! We always write them in RFC 3339 format.
+
+use std::path::Path;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
+
+use super::Error;
+
+/// Formats of date-times (without time-zone) we accept in source data
+const NAIVE_DATE_TIME_FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S"];
+
+/// Formats of dates we accept in source data
+const NAIVE_DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%d.%m.%Y"];
+
+/// Parses a date or date-time in one of the formats commonly found
+/// in manifests.
+/// Values without a time-zone are assumed to be in UTC,
+/// and dates without a time are taken as midnight.
+pub fn parse(raw: &str) -> Option<DateTime<FixedOffset>> {
+    let trimmed = raw.trim();
+    let utc = FixedOffset::east_opt(0)?;
+    DateTime::parse_from_rfc3339(trimmed)
+        .or_else(|_| DateTime::parse_from_rfc2822(trimmed))
+        .ok()
+        .or_else(|| {
+            NAIVE_DATE_TIME_FORMATS
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(trimmed, format).ok())
+                .or_else(|| {
+                    NAIVE_DATE_FORMATS
+                        .iter()
+                        .find_map(|format| NaiveDate::parse_from_str(trimmed, format).ok())
+                        .and_then(|date| date.and_hms_opt(0, 0, 0))
+                })
+                .and_then(|naive| utc.from_local_datetime(&naive).single())
+        })
+}
+
+/// Normalizes a date or date-time to RFC 3339 format.
+pub fn normalize(raw: &str) -> Option<String> {
+    parse(raw).map(|date_time| date_time.to_rfc3339())
+}
+
+/// Returns the commit-time (not author-time)
+/// of the last commit (=> HEAD) of the git repository containing the given file,
+/// but only if that file is tracked in that commit.
+///
+/// # Errors
+///
+/// If some git-related magic goes south.
+pub fn from_git(file: &Path) -> Result<Option<DateTime<FixedOffset>>, Error> {
+    let Ok(abs_file) = file.canonicalize() else {
+        return Ok(None);
+    };
+    let Some(dir) = abs_file.parent() else {
+        return Ok(None);
+    };
+    let Ok(repo) = git2::Repository::discover(dir) else {
+        return Ok(None);
+    };
+    let Some(work_dir) = repo.workdir().and_then(|wd| wd.canonicalize().ok()) else {
+        return Ok(None);
+    };
+    let Ok(rel_file) = abs_file.strip_prefix(work_dir) else {
+        return Ok(None);
+    };
+    if repo.is_empty()? {
+        return Ok(None);
+    }
+    let commit = repo.head()?.peel_to_commit()?;
+    if commit.tree()?.get_path(rel_file).is_err() {
+        log::debug!(
+            "Not using the git commit time, because '{}' is not tracked.",
+            file.display()
+        );
+        return Ok(None);
+    }
+    let commit_time = commit.time();
+    Ok(FixedOffset::east_opt(commit_time.offset_minutes() * 60)
+        .and_then(|offset| offset.timestamp_opt(commit_time.seconds(), 0).single()))
+}
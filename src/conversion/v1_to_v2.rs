@@ -2,43 +2,39 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use std::path::Path;
+use std::{
+    fs,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
 
-use relative_path::RelativePathBuf;
+use relative_path::{RelativePath, RelativePathBuf};
 use url::Url;
 
+use crate::file_types;
+use crate::file_types_format;
 use crate::formats::Locator;
+use crate::formats::ParseError;
 use crate::formats::v1;
+use crate::formats::v1::lenient::{Diagnostic, Recovery};
 use crate::formats::v2;
-use crate::license;
 use crate::oxrl::Odrl;
 use crate::oxrl::Otrl;
 
 use super::Error;
+use super::Options;
+use super::mapping::{FunctionRules, LicenseRules, LicenseSource, OdrlRules, OtrlRules};
+use super::report::Report;
+use super::timestamp;
 
-/// Returns the commit-time (not author-time)
-/// of the last commit in the currently checked out history (=> HEAD)
-///
-/// # Errors
-///
-/// If some git-related magic goes south.
-// fn git_commit_date(date_format: &str) -> BoxResult<Option<String>> {
-fn git_commit_date() -> Result<Option<i64>, Error> {
-    let repo = git2::Repository::open(".");
-    if let Ok(repo_val) = repo {
-        let head = repo_val.head()?;
-        let commit_time_git2 = head.peel_to_commit()?.time();
-        // let commit_time_chrono = DateTime::<Utc>::from_utc(
-        //     NaiveDateTime::from_timestamp(commit_time_git2.seconds(), 0),
-        //     Utc,
-        // );
-        // Ok(Some(commit_time_chrono.format(date_format).to_string()))
-        // date.fromtimestamp(repo.head.ref.commit.committed_date).strftime(date_format)
-        Ok(Some(commit_time_git2.seconds()))
-    } else {
-        Ok(None)
-    }
-}
+/// How deep sub-parts may be nested, before we give up
+const MAX_SUB_PART_DEPTH: usize = 16;
+
+const NOTE_NO_URLS: &str = "OKH LOSH only supports repo relative paths here, no URLs";
+
+/// Without the `v1_non_losh` feature, we do not read the design files,
+/// so no sub-part would have source or export files.
+const NOTE_SUB_PARTS_NEED_DESIGN_FILES: &str = "sub-parts require the design files of their manifests, which are only read with the 'v1_non_losh' feature";
 
 fn shorten_to_repo_url(manifest_url: &Url) -> Option<Url> {
     let repo_path = RelativePathBuf::from(manifest_url.path());
@@ -58,7 +54,16 @@ fn version(v1: &v1::Okh) -> Result<String, Error> {
     )
 }
 
-fn repo(v1: &v1::Okh) -> Result<Url, Error> {
+fn repo(v1: &v1::Okh, report: &mut Report) -> Result<Url, Error> {
+    match (&v1.documentation_home, &v1.project_link) {
+        (Some(_), Some(_)) => {
+            report.mapped("documentation-home", "repo");
+            report.dropped("project-link", "superseded by 'documentation-home'");
+        }
+        (Some(_), None) => report.mapped("documentation-home", "repo"),
+        (None, Some(_)) => report.mapped("project-link", "repo"),
+        (None, None) => (),
+    }
     v1.documentation_home
         .as_ref()
         .or(v1.project_link.as_ref())
@@ -70,23 +75,66 @@ fn repo(v1: &v1::Okh) -> Result<Url, Error> {
     )
 }
 
-fn timestamp(v1: &v1::Okh) -> Option<String> {
-    if let Ok(Some(commit_date)) = git_commit_date() {
-        Some(commit_date.to_string())
-    } else if v1.date_updated.is_some() {
-        v1.date_updated.clone()
-    } else if v1.date_created.is_some() {
-        v1.date_created.clone()
-    } else {
+/// Uses the commit time of the manifest file's own git repo,
+/// if it is tracked there and the lookup is not disabled,
+/// otherwise the first of the parsable OKH v1 dates.
+fn timestamp(
+    v1: &v1::Okh,
+    manifest_file: Option<&Path>,
+    options: &Options,
+    report: &mut Report,
+) -> Result<Option<String>, Error> {
+    let dates = [
+        ("date-updated", &v1.date_updated),
+        ("date-created", &v1.date_created),
+    ];
+    let git_date = if options.no_git_timestamp {
         None
+    } else {
+        manifest_file
+            .map(timestamp::from_git)
+            .transpose()?
+            .flatten()
+    };
+    if let Some(commit_date) = git_date {
+        for (name, date) in dates {
+            if date.is_some() {
+                report.dropped(name, "superseded by the git commit date");
+            }
+        }
+        return Ok(Some(commit_date.to_rfc3339()));
+    }
+    let mut chosen = None;
+    for (name, date) in dates {
+        if let Some(date_val) = date {
+            if chosen.is_some() {
+                report.dropped(name, "superseded by 'date-updated'");
+            } else if let Some(normalized) = timestamp::normalize(date_val) {
+                report.mapped(name, "timestamp");
+                chosen = Some(normalized);
+            } else {
+                report.dropped(
+                    name,
+                    format!("'{date_val}' is not in a supported date format"),
+                );
+            }
+        }
     }
+    Ok(chosen)
 }
 
-fn fork_of(v1: &v1::Okh) -> Result<Option<Url>, Error> {
-    if let Some(parent) = [&v1.derivative_of, &v1.variant_of]
-        .iter()
-        .find_map(|&val| val.as_ref())
+fn fork_of(v1: &v1::Okh, report: &mut Report) -> Result<Option<Url>, Error> {
+    if let Some((name, parent)) = [
+        ("derivative-of", &v1.derivative_of),
+        ("variant-of", &v1.variant_of),
+    ]
+    .iter()
+    .find_map(|&(name, val)| val.as_ref().map(|parent| (name, parent)))
     {
+        report.mapped(name, "fork-of");
+        if v1.derivative_of.is_some() && v1.variant_of.is_some() {
+            report.dropped("variant-of", "superseded by 'derivative-of'");
+        }
         return Ok(if let Some(manifest) = &parent.manifest {
             shorten_to_repo_url(manifest)
         } else if let Some(web) = &parent.web {
@@ -100,56 +148,111 @@ fn fork_of(v1: &v1::Okh) -> Result<Option<Url>, Error> {
     Ok(None)
 }
 
-fn function(v1: &v1::Okh) -> String {
+fn function(v1: &v1::Okh, rules: &FunctionRules, report: &mut Report) -> String {
     let mut function = v1.description.clone();
+    if !function.is_empty() {
+        report.merged("description", "function");
+    }
     if let Some(intended_use) = &v1.intended_use {
         if !function.is_empty() {
-            function.push('\n');
+            function.push_str(&rules.separator);
         }
         function.push_str(intended_use.trim());
+        report.merged("intended-use", "function");
     }
     if let Some(health_safety_notice) = &v1.health_safety_notice {
         if !function.is_empty() {
-            function.push('\n');
+            function.push_str(&rules.separator);
         }
         function.push_str(health_safety_notice.trim());
+        report.merged("health-safety-notice", "function");
     }
     function
 }
 
-const fn documentation_readiness_level(v1: &v1::Okh) -> Odrl {
-    if v1.made_independently {
-        Odrl::Full
+fn documentation_readiness_level(v1: &v1::Okh, rules: &OdrlRules, report: &mut Report) -> Odrl {
+    let odrl = if v1.made_independently {
+        rules.made_independently
     } else {
-        Odrl::Started // TODO HACK What should really be here?
-    }
-}
-
-fn is_prototype(development_stage: Option<&String>) -> bool {
-    development_stage
-        .as_ref()
-        .is_some_and(|ds| ds.to_lowercase() == "prototype")
+        rules.otherwise
+    };
+    report.guessed(
+        "made-independently",
+        "documentation-readiness-level",
+        format!(
+            "{odrl}, because 'made-independently' is {}",
+            v1.made_independently
+        ),
+    );
+    odrl
 }
 
-fn technology_readiness_level(v1: &v1::Okh) -> Otrl {
-    if v1.made_independently || v1.made || is_prototype(v1.development_stage.as_ref()) {
-        Otrl::Development
-    } else {
-        Otrl::Ideation // TODO HACK What should really be here?
+fn technology_readiness_level(v1: &v1::Okh, rules: &OtrlRules, report: &mut Report) -> Otrl {
+    let otrl = [
+        v1.made.then_some(rules.made),
+        v1.made_independently.then_some(rules.made_independently),
+        v1.development_stage
+            .as_ref()
+            .and_then(|stage| rules.for_development_stage(stage)),
+    ]
+    .into_iter()
+    .flatten()
+    .max_by_key(|otrl| u8::from(*otrl))
+    .unwrap_or(rules.otherwise);
+    let note = format!("{otrl}, guessed from 'made', 'made-independently' and 'development-stage'");
+    report.guessed("made", "technology-readiness-level", note.clone());
+    report.guessed(
+        "made-independently",
+        "technology-readiness-level",
+        note.clone(),
+    );
+    if v1.development_stage.is_some() {
+        report.guessed("development-stage", "technology-readiness-level", note);
     }
+    otrl
 }
 
-fn license(v1: &v1::Okh) -> Result<String, Error> {
-    if let Some(lcse) = [&v1.license.hardware, &v1.license.documentation]
-        .iter()
-        .find_map(|&val| val.as_ref())
-    {
-        return Ok(license::ensure_spdx_license_id(lcse));
-    }
-    Err(Error::NoLicense)
+fn license(
+    v1: &v1::Okh,
+    rules: &LicenseRules,
+    report: &mut Report,
+) -> Result<(String, Option<v2::LicenseAspects>), Error> {
+    let candidates = [
+        (
+            LicenseSource::Hardware,
+            "license.hardware",
+            v1.license.hardware.as_deref(),
+        ),
+        (
+            LicenseSource::Documentation,
+            "license.documentation",
+            v1.license.documentation.as_deref(),
+        ),
+        (
+            LicenseSource::Software,
+            "license.software",
+            v1.license.software.as_deref(),
+        ),
+    ];
+    super::combine_licenses(&candidates, rules, report)
 }
 
-fn licensor(v1: &v1::Okh) -> Result<String, Error> {
+fn licensor(v1: &v1::Okh, report: &mut Report) -> Result<String, Error> {
+    if let Some(licensor) = &v1.licensor {
+        if licensor.name.is_some() {
+            report.merged("licensor.name", "licensor");
+        }
+        if licensor.email.is_some() {
+            report.merged("licensor.email", "licensor");
+        }
+        if licensor.affiliation.is_some() {
+            report.dropped("licensor.affiliation", "not supported by OKH LOSH");
+        }
+        #[cfg(feature = "v1_non_losh")]
+        if !licensor.social.is_empty() {
+            report.dropped("licensor.social", "not supported by OKH LOSH");
+        }
+    }
     v1.licensor.as_ref().map_or(
         Err(Error::InsufficientData {
             msg: "OKH v1 'licensor' is required to convert to OKH LOSH",
@@ -178,9 +281,10 @@ fn licensor(v1: &v1::Okh) -> Result<String, Error> {
     )
 }
 
-fn software(v1: &v1::Okh) -> Vec<v2::Software> {
+fn software(v1: &v1::Okh, report: &mut Report) -> Vec<v2::Software> {
     let mut software = Vec::<v2::Software>::new();
-    for sw in &v1.software {
+    for (idx, sw) in v1.software.iter().enumerate() {
+        report.mapped(format!("software[{idx}]"), "software");
         software.push(v2::Software {
             label: sw.title.clone(),
             release: sw
@@ -192,9 +296,14 @@ fn software(v1: &v1::Okh) -> Vec<v2::Software> {
     software
 }
 
-fn standard(v1: &v1::Okh) -> Vec<v2::DSString> {
+fn standard(v1: &v1::Okh, report: &mut Report) -> Vec<v2::DSString> {
     let mut standards = Vec::<v2::DSString>::new();
-    for standard in &v1.standards_used {
+    for (idx, standard) in v1.standards_used.iter().enumerate() {
+        report.mapped(format!("standards-used[{idx}]"), "standard");
+        report.mapped(
+            format!("standards-used[{idx}].standard-title"),
+            "standard-compliance",
+        );
         if !standard.reference.is_empty() {
             standards.push(standard.reference.trim().to_string());
         }
@@ -205,51 +314,213 @@ fn standard(v1: &v1::Okh) -> Vec<v2::DSString> {
     standards
 }
 
-fn sub_mosh(v1: &v1::Okh) -> Result<Vec<v2::SubMosh>, Error> {
+/// Tries to find a local copy of the manifest of a sub-part,
+/// either within the local checkout of the project (`repo_dir`),
+/// or in the manifest cache.
+/// Returns the path to the manifest,
+/// and whether it is part of the local checkout.
+fn find_sub_manifest(
+    sub_part: &v1::OtherThing,
+    manifest_file: Option<&RelativePathBuf>,
+    repo_dir: Option<&Path>,
+    options: &Options,
+) -> Option<(PathBuf, bool)> {
+    let local = manifest_file
+        .zip(repo_dir)
+        .map(|(rel_path, dir)| (rel_path.to_path(dir), true));
+    let cached = options
+        .manifest_cache
+        .as_ref()
+        .zip(sub_part.manifest.as_ref())
+        .and_then(|(cache_dir, url)| {
+            url.host_str().map(|host| {
+                (
+                    cache_dir
+                        .join(host)
+                        .join(url.path().trim_start_matches('/')),
+                    false,
+                )
+            })
+        });
+    [local, cached]
+        .into_iter()
+        .flatten()
+        .find(|(candidate, _)| candidate.is_file())
+}
+
+/// Makes the paths of a part relative to the repo root,
+/// instead of to the parts own manifest.
+/// This is not applied to the sub-parts of the part,
+/// as they are already relative to the repo root.
+fn prefix_paths(part: &mut v2::SubMosh, prefix: &RelativePath) {
+    for paths in [
+        &mut part.image,
+        &mut part.source,
+        &mut part.export,
+        &mut part.auxiliary,
+    ] {
+        for path in paths.iter_mut() {
+            *path = prefix.join_normalized(&*path);
+        }
+    }
+}
+
+/// Converts the manifest of a single sub-part.
+///
+/// Returns `Err` with the reason,
+/// in case the sub-part can not be represented in OKH LOSH.
+fn convert_sub_part(
+    sub_part: &v1::OtherThing,
+    manifest_file: Option<&RelativePathBuf>,
+    repo_dir: Option<&Path>,
+    options: &Options,
+    visited: &[PathBuf],
+) -> Result<(v2::SubMosh, Report), String> {
+    let (sub_manifest, is_local) = find_sub_manifest(sub_part, manifest_file, repo_dir, options).ok_or(
+        "its manifest was found neither locally nor in the manifest cache (see --manifest-cache)",
+    )?;
+    let sub_manifest_canon = sub_manifest
+        .canonicalize()
+        .map_err(|err| format!("failed to resolve '{}': {err}", sub_manifest.display()))?;
+    if visited.contains(&sub_manifest_canon) {
+        return Err(format!(
+            "cyclic sub-part reference to '{}'",
+            sub_manifest.display()
+        ));
+    }
+    if visited.len() >= MAX_SUB_PART_DEPTH {
+        return Err(format!(
+            "sub-parts are nested deeper than {MAX_SUB_PART_DEPTH} levels"
+        ));
+    }
+    log::debug!(
+        "Converting sub-part manifest '{}' ...",
+        sub_manifest.display()
+    );
+    let sub_v1 = fs::File::open(&sub_manifest)
+        .map_err(ParseError::from)
+        .and_then(|input| parse(input, options))
+        .map(|(sub_v1, diagnostics)| {
+            for diagnostic in diagnostics {
+                log::warn!("{}: {diagnostic}", sub_manifest.display());
+            }
+            sub_v1
+        })
+        .map_err(|err| format!("failed to parse '{}': {err}", sub_manifest.display()))?;
+    let mut sub_visited = visited.to_vec();
+    sub_visited.push(sub_manifest_canon);
+    // NOTE For sub-parts from the cache (other repos),
+    //      we treat the dir containing the manifest as their repo root.
+    let sub_repo_dir = if is_local {
+        repo_dir
+    } else {
+        sub_manifest.parent()
+    };
+    let (sub_v2, sub_report) = convert_rec(sub_v1, sub_repo_dir, options, &sub_visited)
+        .map_err(|err| format!("failed to convert '{}': {err}", sub_manifest.display()))?;
+    if sub_v2.source.is_empty() && sub_v2.export.is_empty() {
+        return Err(format!(
+            "its manifest ('{}') lists neither source nor export files",
+            sub_manifest.display()
+        ));
+    }
+    let mut part = v2::SubMosh {
+        name: sub_part
+            .title
+            .as_ref()
+            .map(|title| title.trim().to_string())
+            .or(Some(sub_v2.name)),
+        image: sub_v2.image,
+        tsdc: sub_v2.tsdc,
+        source: sub_v2.source,
+        export: sub_v2.export,
+        auxiliary: sub_v2.auxiliary,
+        part: sub_v2.part,
+    };
+    // Paths within sub-parts of the same repo are made relative to the repo root.
+    // NOTE For sub-parts from the cache (other repos), they stay relative to their own repo.
+    if is_local && let Some(prefix) = manifest_file.and_then(|rel_path| rel_path.parent()) {
+        prefix_paths(&mut part, prefix);
+    }
+    Ok((part, sub_report))
+}
+
+/// Adjusts the report of converting the manifest of a sub-part,
+/// reporting everything [`v2::SubMosh`] has no place for as dropped.
+/// If the sub-part has a title, it is used as the name
+/// instead of the one from its manifest.
+fn sub_part_report(sub_report: Report, has_title: bool) -> Report {
+    let mut part_report = Report::new(sub_report.from, sub_report.to);
+    for field in sub_report.fields {
+        match field.target {
+            Some("name") if has_title => {
+                part_report.dropped(field.source, "the title of the sub-part is used instead");
+            }
+            Some(target) if !v2::SUB_MOSH_KEYS.contains(&target) => {
+                part_report.dropped(
+                    field.source,
+                    format!("sub-parts have no '{target}' in OKH LOSH"),
+                );
+            }
+            Some(_) | None => part_report.fields.push(field),
+        }
+    }
+    part_report
+}
+
+/// Returns the path of `url` relative to `main_url`,
+/// if it lies within it.
+fn repo_relative(url: &str, main_url: &str) -> Option<RelativePathBuf> {
+    url.strip_prefix(main_url.trim_end_matches('/'))?
+        .strip_prefix('/')
+        .map(RelativePathBuf::from)
+}
+
+fn sub_mosh(
+    v1: &v1::Okh,
+    repo_dir: Option<&Path>,
+    options: &Options,
+    visited: &[PathBuf],
+    report: &mut Report,
+) -> Result<Vec<v2::SubMosh>, Error> {
     let mut sub_mosh = Vec::<v2::SubMosh>::new();
-    for parent in &v1.sub_parts {
-        let repo = if let Some(manifest) = &parent.manifest {
-            shorten_to_repo_url(manifest)
-        } else if let Some(web) = &parent.web {
-            Some(web.clone())
-        } else {
+    if cfg!(not(feature = "v1_non_losh")) && !v1.sub_parts.is_empty() {
+        log::warn!(
+            "Dropping all {} sub-parts, because {NOTE_SUB_PARTS_NEED_DESIGN_FILES}",
+            v1.sub_parts.len()
+        );
+    }
+    for (idx, sub_part) in v1.sub_parts.iter().enumerate() {
+        if sub_part.manifest.is_none() && sub_part.web.is_none() {
             return Err(Error::InsufficientData {
                 msg: "For 'sub(-part)', at least one of 'web' and 'manifest' needs to be specified",
             });
-        };
-        let name = parent.title.clone().map(|s| s.trim().to_string());
+        }
+        if cfg!(not(feature = "v1_non_losh")) {
+            report.dropped(
+                format!("sub-parts[{idx}]"),
+                NOTE_SUB_PARTS_NEED_DESIGN_FILES,
+            );
+            continue;
+        }
         let main_url = v1.main_url().map(Url::to_string);
-        let manifest_file = parent.manifest.clone().and_then(|mf_url| {
-            main_url.and_then(|main_url_val| {
-                let mf_url_str = mf_url.to_string();
-                mf_url_str
-                    .strip_prefix(&main_url_val)
-                    .map(RelativePathBuf::from)
-            })
+        let manifest_file = sub_part.manifest.as_ref().and_then(|mf_url| {
+            main_url.and_then(|main_url_val| repo_relative(mf_url.as_str(), &main_url_val))
         });
-        let image = if let Some(image) = v1.image.as_ref() {
-            vec![image.clone()]
-        } else {
-            vec![]
-        };
-        let tsdc = None;
-        let source = vec![]; // TODO This or export is required
-        let export = vec![]; // TODO This or source is required
-        let auxiliary = vec![];
-        let part = vec![];
-        if false {
-            // TODO As neither source nor export is set, we have to skip this for now, as otherwise we'd generate an invalid manifest
-            sub_mosh.push(v2::SubMosh {
-                name,
-                // manifest_file,
-                // repo,
-                image,
-                tsdc,
-                source,
-                export,
-                auxiliary,
-                part,
-            });
+        let source = format!("sub-parts[{idx}]");
+        match convert_sub_part(sub_part, manifest_file.as_ref(), repo_dir, options, visited) {
+            Ok((part, sub_report)) => {
+                report.mapped(source.clone(), "part");
+                report.extend_prefixed(
+                    &source,
+                    sub_part_report(sub_report, sub_part.title.is_some()),
+                );
+                sub_mosh.push(part);
+            }
+            Err(reason) => {
+                log::warn!("Dropping {source}, because {reason}");
+                report.dropped(source, reason);
+            }
         }
     }
     Ok(sub_mosh)
@@ -259,21 +530,219 @@ fn sub_mosh(v1: &v1::Okh) -> Result<Vec<v2::SubMosh>, Error> {
 //     todo!()
 // }
 
-fn collect_doc_paths(docs: &'_ [v1::Document]) -> impl '_ + Iterator<Item = RelativePathBuf> {
-    docs.iter().filter_map(|doc| match &doc.path {
-        Some(Locator::Path(path)) => Some(path.clone()),
-        _ => None,
-    })
+/// Collects the paths of up to `max` documents,
+/// and reports what happens to each of them.
+fn collect_doc_paths(
+    docs: &[v1::Document],
+    source: &str,
+    target: &'static str,
+    max: usize,
+    report: &mut Report,
+) -> Vec<RelativePathBuf> {
+    let mut paths = vec![];
+    for (idx, doc) in docs.iter().enumerate() {
+        let doc_source = format!("{source}[{idx}]");
+        match &doc.path {
+            Some(Locator::Path(path)) if paths.len() < max => {
+                report.mapped(doc_source.clone(), target);
+                paths.push(path.clone());
+            }
+            Some(Locator::Path(_)) => {
+                report.dropped(
+                    doc_source.clone(),
+                    format!("OKH LOSH supports only {max} of these"),
+                );
+            }
+            Some(Locator::Url(_)) => report.dropped(doc_source.clone(), NOTE_NO_URLS),
+            None => report.dropped(doc_source.clone(), "no path given"),
+        }
+        if doc.title.is_some() {
+            report.dropped(
+                format!("{doc_source}.title"),
+                "document titles are not supported by OKH LOSH",
+            );
+        }
+    }
+    paths
+}
+
+fn collect_doc_path(
+    docs: &[v1::Document],
+    source: &str,
+    target: &'static str,
+    report: &mut Report,
+) -> Option<RelativePathBuf> {
+    collect_doc_paths(docs, source, target, 1, report)
+        .into_iter()
+        .next()
+}
+
+/// The design files of a project,
+/// sorted into the OKH LOSH categories.
+#[derive(Default)]
+struct DesignFiles {
+    source: Vec<RelativePathBuf>,
+    export: Vec<RelativePathBuf>,
+    auxiliary: Vec<RelativePathBuf>,
+}
+
+impl DesignFiles {
+    /// Sorts a file into source, export or auxiliary,
+    /// judging by its file extension.
+    /// Files of unknown type end up as auxiliary.
+    fn add(&mut self, path: &RelativePathBuf, source: String, report: &mut Report) {
+        let format = path.extension().and_then(file_types::find_by_ext);
+        let (files, target) = match format.map(|fmt| fmt.source) {
+            Some(file_types_format::Source::Source) => (&mut self.source, "source"),
+            Some(file_types_format::Source::Export) => (&mut self.export, "export"),
+            None => (&mut self.auxiliary, "auxiliary"),
+        };
+        report.mapped(source, target);
+        if !files.contains(path) {
+            files.push(path.clone());
+        }
+    }
+}
+
+/// Collects the design files, schematics, manufacturing files and tool settings,
+/// and sorts them into source, export and auxiliary files.
+#[cfg(feature = "v1_non_losh")]
+fn design_files(v1: &v1::Okh, report: &mut Report) -> DesignFiles {
+    let mut design_files = DesignFiles::default();
+    for (idx, path) in v1.design_files.iter().enumerate() {
+        design_files.add(path, format!("design-files[{idx}]"), report);
+    }
+    for (name, docs) in [
+        ("schematics", &v1.schematics),
+        ("manufacturing-files", &v1.manufacturing_files),
+        ("tool-settings", &v1.tool_settings),
+    ] {
+        for (idx, doc) in docs.iter().enumerate() {
+            let doc_source = format!("{name}[{idx}]");
+            if doc.title.is_some() {
+                report.dropped(
+                    format!("{doc_source}.title"),
+                    "document titles are not supported by OKH LOSH",
+                );
+            }
+            match &doc.path {
+                Some(Locator::Path(path)) => design_files.add(path, doc_source, report),
+                Some(Locator::Url(_)) => report.dropped(doc_source, NOTE_NO_URLS),
+                None => report.dropped(doc_source, "no path given"),
+            }
+        }
+    }
+    design_files
+}
+
+/// Without the `v1_non_losh` feature,
+/// we do not parse any design files.
+#[cfg(not(feature = "v1_non_losh"))]
+fn design_files(_v1: &v1::Okh, _report: &mut Report) -> DesignFiles {
+    DesignFiles::default()
 }
 
-fn collect_doc_path(docs: &[v1::Document]) -> Option<RelativePathBuf> {
-    collect_doc_paths(docs).next() // TODO FIXME This ignores all but the first document with a path!
+/// Reports all the properties that are only known to OKH v1,
+/// and thus get lost in the conversion.
+fn report_v1_only(v1: &v1::Okh, report: &mut Report) {
+    let note = "not supported by OKH LOSH";
+    if !v1.keywords.is_empty() {
+        report.dropped("keywords", note);
+    }
+    #[cfg(feature = "v1_non_losh")]
+    for (name, is_set) in [
+        ("manifest-author", v1.manifest_author.is_some()),
+        ("manifest-language", v1.manifest_language.is_some()),
+        (
+            "manifest-is-translation",
+            v1.manifest_is_translation.is_some(),
+        ),
+        ("contact", v1.contact.is_some()),
+        ("contributors", !v1.contributors.is_empty()),
+        (
+            "documentation-is-translation",
+            v1.documentation_is_translation.is_some(),
+        ),
+        ("tools-list", v1.tools_list.is_some()),
+        ("risk-assessment", !v1.risk_assessment.is_empty()),
+        ("quality-instructions", !v1.quality_instructions.is_empty()),
+        (
+            "maintenance-instructions",
+            !v1.maintenance_instructions.is_empty(),
+        ),
+        (
+            "disposal-instructions",
+            !v1.disposal_instructions.is_empty(),
+        ),
+    ] {
+        if is_set {
+            report.dropped(name, note);
+        }
+    }
 }
 
-pub fn convert(v1: v1::Okh) -> Result<v2::Okh, Error> {
+/// Reports the properties that are mapped one to one.
+fn report_plain_mappings(v1: &v1::Okh, report: &mut Report) {
+    for (name, target, is_set) in [
+        ("image", "image", v1.image.is_some()),
+        (
+            "okh-manifest-version",
+            "okhv",
+            v1.manifest_version.is_some(),
+        ),
+        (
+            "documentation-language",
+            "documentation-language",
+            v1.documentation_language.is_some(),
+        ),
+        ("bom", "bom", v1.bom.is_some()),
+        ("archive-download", "release", v1.archive_download.is_some()),
+    ] {
+        if is_set {
+            report.mapped(name, target);
+        }
+    }
+}
+
+/// Converts an OKH v1 manifest to an OKH LOSH one.
+///
+/// `manifest_file` is the local file the manifest was read from, if any.
+/// Its directory is assumed to be the root of the local project checkout,
+/// and is used to find the manifests of sub-parts.
+/// Next to the converted manifest,
+/// this returns a report of what happened to each OKH v1 property.
+///
+/// # Errors
+///
+/// If the OKH v1 manifest lacks data that is required by OKH LOSH.
+pub fn convert(
+    v1: v1::Okh,
+    manifest_file: Option<&Path>,
+    options: &Options,
+) -> Result<(v2::Okh, Report), Error> {
+    let repo_dir = manifest_file.and_then(Path::parent);
+    let visited: Vec<PathBuf> = manifest_file
+        .and_then(|file| file.canonicalize().ok())
+        .into_iter()
+        .collect();
+    convert_rec(v1, repo_dir, options, &visited)
+}
+
+/// Does the actual work for [`convert`],
+/// keeping track of the manifests we are currently within,
+/// to be able to detect cyclic sub-part references.
+fn convert_rec(
+    v1: v1::Okh,
+    repo_dir: Option<&Path>,
+    options: &Options,
+    visited: &[PathBuf],
+) -> Result<(v2::Okh, Report), Error> {
     log::debug!("Converting OKH v1 to v2 ...");
+    let mut report = Report::new(v1::FORMAT_NAME, v2::FORMAT_NAME);
+    report.mapped("title", "name");
     let version = version(&v1)?;
-    let repo = repo(&v1)?;
+    report.mapped("version", "version");
+    let repo = repo(&v1, &mut report)?;
     let organisation = None;
     let readme = None;
     let contribution_guide = None;
@@ -285,21 +754,49 @@ pub fn convert(v1: v1::Okh) -> Result<v2::Okh, Error> {
         .collect();
     let cpc_patent_class = None;
     let tsdc = None;
-    let timestamp = timestamp(&v1);
-    let fork_of = fork_of(&v1)?;
-    let function = Some(function(&v1));
-    let documentation_readiness_level = Some(documentation_readiness_level(&v1));
-    let technology_readiness_level = Some(technology_readiness_level(&v1));
-    let license = license(&v1)?;
-    let licensor = licensor(&v1)?;
-    let manufacturing_instructions = collect_doc_paths(&v1.making_instructions).collect();
-    let user_manual = collect_doc_path(&v1.operating_instructions); // TODO FIXME This ignores all but the first operating instruction!
-    let software = software(&v1);
-    let standard = standard(&v1);
-    let source = vec![]; // TODO see v1.design_files and v1.schematics, but split into source, export and auxiliary!
-    let export = vec![]; // TODO see v1.design_files and v1.schematics, but split into source, export and auxiliary!
-    let auxiliary = vec![]; // TODO see v1.design_files and v1.schematics, but split into source, export and auxiliary!
-    let part = sub_mosh(&v1)?;
+    let timestamp = timestamp(
+        &v1,
+        visited.last().map(PathBuf::as_path),
+        options,
+        &mut report,
+    )?;
+    let fork_of = fork_of(&v1, &mut report)?;
+    let function = Some(function(&v1, &options.rules.function, &mut report));
+    let documentation_readiness_level = Some(documentation_readiness_level(
+        &v1,
+        &options.rules.odrl,
+        &mut report,
+    ));
+    let technology_readiness_level = Some(technology_readiness_level(
+        &v1,
+        &options.rules.otrl,
+        &mut report,
+    ));
+    let (license, license_aspects) = license(&v1, &options.rules.license, &mut report)?;
+    let licensor = licensor(&v1, &mut report)?;
+    let manufacturing_instructions = collect_doc_paths(
+        &v1.making_instructions,
+        "making-instructions",
+        "manufacturing-instructions",
+        usize::MAX,
+        &mut report,
+    );
+    let user_manual = collect_doc_path(
+        &v1.operating_instructions,
+        "operating-instructions",
+        "user-manual",
+        &mut report,
+    );
+    let software = software(&v1, &mut report);
+    let standard = standard(&v1, &mut report);
+    let DesignFiles {
+        source,
+        export,
+        auxiliary,
+    } = design_files(&v1, &mut report);
+    let part = sub_mosh(&v1, repo_dir, options, visited, &mut report)?;
+    report_v1_only(&v1, &mut report);
+    report_plain_mappings(&v1, &mut report);
     let image = v1.image.into_iter().collect();
     let upload_method = Some("manifest-script".to_string()); // TODO cleanup this whole property in the specs
     // let __meta = v2::Meta {
@@ -309,7 +806,7 @@ pub fn convert(v1: v1::Okh) -> Result<v2::Okh, Error> {
     //     path: None,
     // };
 
-    Ok(v2::Okh {
+    let v2 = v2::Okh {
         okhv: v2::OKHV.to_owned(),
         upload_method,
         name: v1.title.trim().to_string(),
@@ -340,29 +837,48 @@ pub fn convert(v1: v1::Okh) -> Result<v2::Okh, Error> {
         source,
         export,
         auxiliary,
+        license_aspects,
         part,
         // __meta,
-    })
+    };
+    Ok((v2, report))
 }
 
-pub fn convert_file<IP, OP>(yaml_file: IP, toml_file: OP) -> Result<(), Error>
-where
-    IP: AsRef<Path>,
-    OP: AsRef<Path>,
-{
-    log::info!(
-        "OKH v1 (YAML) input file:  {}",
-        yaml_file.as_ref().display()
-    );
-    log::info!(
-        "OKH v2 (TOML) output file: {}",
-        toml_file.as_ref().display()
-    );
-
-    let v1 = v1::Okh::from_yaml_file(yaml_file)?;
-    let v2 = convert(v1)?;
-    v2.to_toml_file(toml_file)?;
+/// Parses an OKH v1 manifest,
+/// leniently if so requested in the options.
+fn parse<R: Read>(input: R, options: &Options) -> Result<(v1::Okh, Vec<Diagnostic>), ParseError> {
+    if options.lenient {
+        v1::Okh::from_yaml_reader_lenient(input)
+    } else {
+        v1::Okh::from_yaml_reader(input).map(|parsed| (parsed, vec![]))
+    }
+}
 
-    log::info!("done.");
-    Ok(())
+/// Converts an OKH v1 manifest read from `input`,
+/// writing the OKH LOSH manifest to `output`.
+///
+/// `manifest_file` is the local file the input was read from, if any;
+/// see [`convert`].
+///
+/// # Errors
+///
+/// If reading, converting or writing fails.
+pub fn convert_io<R: Read, W: Write>(
+    input: R,
+    manifest_file: Option<&Path>,
+    output: W,
+    options: &Options,
+) -> Result<Report, Error> {
+    let (v1, diagnostics) = parse(input, options)?;
+    for diagnostic in &diagnostics {
+        log::warn!("{diagnostic}");
+    }
+    let (v2, mut report) = convert(v1, manifest_file, options)?;
+    for diagnostic in diagnostics {
+        if diagnostic.recovery == Recovery::Dropped {
+            report.dropped(diagnostic.affected, diagnostic.message);
+        }
+    }
+    v2.to_toml_writer(output)?;
+    Ok(report)
 }
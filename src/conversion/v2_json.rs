@@ -0,0 +1,39 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Conversions between the TOML and the JSON(-LD) serializations
+//! of OKH LOSH manifests.
+//! As they all share the same data model,
+//! the conversions between TOML and JSON are lossless.
+//! JSON-LD lacks the properties that have no counterpart
+//! in the OKH LOSH ontology.
+
+use std::io::{Read, Write};
+
+use crate::formats::{rdf, v2};
+
+use super::Error;
+use super::report::Report;
+
+pub fn toml_to_json_io<R: Read, W: Write>(input: R, output: W) -> Result<Report, Error> {
+    let v2 = v2::Okh::from_toml_reader(input)?;
+    v2.to_json_writer(output)?;
+    Ok(Report::new(v2::FORMAT_NAME, v2::FORMAT_NAME_JSON))
+}
+
+pub fn toml_to_jsonld_io<R: Read, W: Write>(input: R, output: W) -> Result<Report, Error> {
+    let v2 = v2::Okh::from_toml_reader(input)?;
+    v2.to_jsonld_writer(output)?;
+    let mut report = Report::new(v2::FORMAT_NAME, v2::FORMAT_NAME_JSONLD);
+    if v2.license_aspects.is_some() {
+        report.dropped(rdf::UNMAPPED_PROP, rdf::UNMAPPED_NOTE);
+    }
+    Ok(report)
+}
+
+pub fn json_to_toml_io<R: Read, W: Write>(input: R, output: W) -> Result<Report, Error> {
+    let v2 = v2::Okh::from_json_reader(input)?;
+    v2.to_toml_writer(output)?;
+    Ok(Report::new(v2::FORMAT_NAME_JSON, v2::FORMAT_NAME))
+}
@@ -0,0 +1,187 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Converts OKH LOSH manifests into software citation meta-data,
+//! namely `CITATION.cff` and `codemeta.json` files.
+//! Only the few properties relevant for citing are exported.
+
+use std::io::{Read, Write};
+
+use url::Url;
+
+use crate::formats::citation::{self, Cff, CffEntity, CodeMeta, CodeMetaPerson};
+use crate::formats::v2;
+use crate::license;
+
+use super::Error;
+use super::report::Report;
+use super::timestamp;
+
+const CFF_MESSAGE: &str =
+    "If you use this hardware, please cite it using the metadata from this file.";
+const SPDX_LICENSE_BASE_URL: &str = "https://spdx.org/licenses/";
+
+/// Extracts the date (`YYYY-MM-DD`) from the timestamp.
+fn date(raw: &str) -> Option<String> {
+    timestamp::parse(raw).map(|date_time| date_time.format("%Y-%m-%d").to_string())
+}
+
+/// Reports what happens to each of the OKH LOSH properties.
+fn report(
+    v2: &v2::Okh,
+    to_format: &'static str,
+    targets: [&'static str; 6],
+    license_ids: Option<&Vec<&str>>,
+    date: Option<&String>,
+) -> Report {
+    let mut report = Report::new(v2::FORMAT_NAME, to_format);
+    let [name, version, licensor, license, repo, release] = targets;
+    report.mapped("name", name);
+    report.mapped("version", version);
+    report.mapped("licensor", licensor);
+    if license_ids.is_some() {
+        report.mapped("license", license);
+    } else {
+        report.dropped(
+            "license",
+            format!(
+                "'{}' is not a single SPDX listed license ID, nor a choice (OR) between such",
+                v2.license
+            ),
+        );
+    }
+    report.mapped("repo", repo);
+    if v2.release.is_some() {
+        report.mapped("release", release);
+    }
+    if let Some(timestamp) = &v2.timestamp
+        && date.is_none()
+    {
+        report.dropped(
+            "timestamp",
+            format!("'{timestamp}' is not in a supported date format"),
+        );
+    }
+    report
+}
+
+/// Converts an OKH LOSH manifest into a Citation File Format structure.
+pub fn to_cff(v2: &v2::Okh) -> (Cff, Report) {
+    log::debug!("Converting OKH v2 to CFF ...");
+    let license_ids = license::spdx_license_alternatives(&v2.license);
+    let date_released = v2.timestamp.as_deref().and_then(date);
+    let mut report = report(
+        v2,
+        citation::FORMAT_NAME_CFF,
+        [
+            "title",
+            "version",
+            "authors",
+            "license",
+            "repository-code",
+            "repository-artifact",
+        ],
+        license_ids.as_ref(),
+        date_released.as_ref(),
+    );
+    if date_released.is_some() {
+        report.mapped("timestamp", "date-released");
+    }
+
+    let (name, email) = v2.licensor_name_and_email();
+    let author = CffEntity {
+        // CFF requires a name for entities
+        name: name.or(email).unwrap_or_default().to_owned(),
+        email: email.map(ToOwned::to_owned),
+    };
+    let cff = Cff {
+        format_version: citation::CFF_VERSION.to_owned(),
+        message: CFF_MESSAGE.to_owned(),
+        title: v2.name.clone(),
+        version: v2.version.clone(),
+        license: license_ids
+            .unwrap_or_default()
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect(),
+        authors: vec![author],
+        repository_code: v2.repo.clone(),
+        repository_artifact: v2.release.clone(),
+        date_released,
+    };
+    (cff, report)
+}
+
+/// Converts an OKH LOSH manifest into a `CodeMeta` structure.
+pub fn to_codemeta(v2: &v2::Okh) -> (CodeMeta, Report) {
+    log::debug!("Converting OKH v2 to CodeMeta ...");
+    let license_ids = license::spdx_license_alternatives(&v2.license);
+    let date_modified = v2.timestamp.as_deref().and_then(date);
+    let mut report = report(
+        v2,
+        citation::FORMAT_NAME_CODEMETA,
+        [
+            "name",
+            "version",
+            "author",
+            "license",
+            "codeRepository",
+            "downloadUrl",
+        ],
+        license_ids.as_ref(),
+        date_modified.as_ref(),
+    );
+    if date_modified.is_some() {
+        report.mapped("timestamp", "dateModified");
+    }
+
+    let (name, email) = v2.licensor_name_and_email();
+    let author = CodeMetaPerson {
+        kind: "Person".to_owned(),
+        name: name.map(ToOwned::to_owned),
+        email: email.map(ToOwned::to_owned),
+    };
+    let codemeta = CodeMeta {
+        context: citation::CODEMETA_CONTEXT.to_owned(),
+        kind: "SoftwareSourceCode".to_owned(),
+        name: v2.name.clone(),
+        version: v2.version.clone(),
+        license: license_ids
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|id| Url::parse(&format!("{SPDX_LICENSE_BASE_URL}{id}")).ok())
+            .collect(),
+        author: vec![author],
+        code_repository: v2.repo.clone(),
+        download_url: v2.release.clone(),
+        date_modified,
+    };
+    (codemeta, report)
+}
+
+/// Converts an OKH LOSH manifest read from `input`,
+/// writing the CFF to `output`.
+///
+/// # Errors
+///
+/// If reading, parsing or writing fails.
+pub fn convert_io_cff<R: Read, W: Write>(input: R, output: W) -> Result<Report, Error> {
+    let v2 = v2::Okh::from_toml_reader(input)?;
+    let (cff, report) = to_cff(&v2);
+    cff.to_yaml_writer(output)?;
+    Ok(report)
+}
+
+/// Converts an OKH LOSH manifest read from `input`,
+/// writing the `CodeMeta` JSON to `output`.
+///
+/// # Errors
+///
+/// If reading, parsing or writing fails.
+pub fn convert_io_codemeta<R: Read, W: Write>(input: R, output: W) -> Result<Report, Error> {
+    let v2 = v2::Okh::from_toml_reader(input)?;
+    let (codemeta, report) = to_codemeta(&v2);
+    codemeta.to_json_writer(output)?;
+    Ok(report)
+}
@@ -0,0 +1,375 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::io::{Read, Write};
+
+use oxrdf::vocab::{rdf, rdfs};
+use oxrdf::{Literal, NamedNode, Term, Triple};
+use oxttl::TurtleSerializer;
+use relative_path::RelativePathBuf;
+use serde::Serialize;
+use url::Url;
+
+use crate::formats::SerError;
+use crate::formats::rdf::{
+    self as okh_rdf, NS_ODRL, NS_OKH, NS_OTRL, NS_RDFS, class, okh_node, prop,
+};
+use crate::formats::v2;
+
+use super::Error;
+use super::report::Report;
+
+fn url_node(url: &Url) -> NamedNode {
+    NamedNode::new_unchecked(url.as_str())
+}
+
+/// Creates the IRI of a (sub-)module or software,
+/// see [`okh_rdf::module_iri`].
+fn module_node(repo: &Url, fragment: &str) -> NamedNode {
+    url_node(&okh_rdf::module_iri(repo, fragment))
+}
+
+/// Creates the IRI of an ODRL or OTRL individual,
+/// using the same name as in the TOML serialization (e.g. `OTRL-4`).
+fn oxrl_node<T: Serialize>(namespace: &str, level: &T) -> Option<NamedNode> {
+    serde_json::to_value(level)
+        .ok()
+        .and_then(|value| value.as_str().map(|name| format!("{namespace}{name}")))
+        .map(NamedNode::new_unchecked)
+}
+
+/// Collects the triples representing a manifest.
+#[derive(Default)]
+struct Graph {
+    triples: Vec<Triple>,
+}
+
+impl Graph {
+    fn add<O: Into<Term>>(&mut self, subject: &NamedNode, predicate: &str, object: O) {
+        self.triples
+            .push(Triple::new(subject.clone(), okh_node(predicate), object));
+    }
+
+    fn add_str(&mut self, subject: &NamedNode, predicate: &str, value: &str) {
+        self.add(subject, predicate, Literal::new_simple_literal(value));
+    }
+
+    fn add_opt_str(&mut self, subject: &NamedNode, predicate: &str, value: Option<&String>) {
+        if let Some(value_val) = value {
+            self.add_str(subject, predicate, value_val);
+        }
+    }
+
+    /// Adds file references as IRIs within the repo (see [`okh_rdf::file_iri`]),
+    /// or as plain literals, if they lead out of the repo.
+    fn add_paths<'a, I>(&mut self, subject: &NamedNode, predicate: &str, repo: &Url, paths: I)
+    where
+        I: IntoIterator<Item = &'a RelativePathBuf>,
+    {
+        for path in paths {
+            if let Some(iri) = okh_rdf::file_iri(repo, path) {
+                self.add(subject, predicate, url_node(&iri));
+            } else {
+                log::warn!(
+                    "Keeping the file path '{path}' as a literal, as it leads out of the repo"
+                );
+                self.add_str(subject, predicate, path.as_str());
+            }
+        }
+    }
+
+    fn add_type(&mut self, subject: &NamedNode, class_name: &str) {
+        self.triples.push(Triple::new(
+            subject.clone(),
+            rdf::TYPE,
+            okh_node(class_name),
+        ));
+    }
+
+    /// Links the parts to their (sub-)module,
+    /// returning their nodes and IRI fragments.
+    /// The parts themselves are added with [`Self::add_parts`].
+    fn link_parts<'a>(
+        &mut self,
+        subject: &NamedNode,
+        repo: &Url,
+        fragment: &str,
+        parts: &'a [v2::SubMosh],
+    ) -> Vec<(NamedNode, String, &'a v2::SubMosh)> {
+        parts
+            .iter()
+            .enumerate()
+            .map(|(idx, part)| {
+                let part_fragment = format!("{fragment}-{idx}");
+                let part_node = module_node(repo, &part_fragment);
+                self.add(subject, prop::PART, part_node.clone());
+                (part_node, part_fragment, part)
+            })
+            .collect()
+    }
+
+    /// Adds parts previously linked with [`Self::link_parts`], recursively.
+    fn add_parts(&mut self, repo: &Url, parts: Vec<(NamedNode, String, &v2::SubMosh)>) {
+        for (part_node, part_fragment, part) in parts {
+            self.add_type(&part_node, class::MODULE);
+            self.add_opt_str(&part_node, prop::NAME, part.name.as_ref());
+            self.add_paths(&part_node, prop::IMAGE, repo, &part.image);
+            self.add_opt_str(&part_node, prop::TSDC, part.tsdc.as_ref());
+            self.add_paths(&part_node, prop::SOURCE, repo, &part.source);
+            self.add_paths(&part_node, prop::EXPORT, repo, &part.export);
+            self.add_paths(&part_node, prop::AUXILIARY, repo, &part.auxiliary);
+            let sub_parts = self.link_parts(&part_node, repo, &part_fragment, &part.part);
+            self.add_parts(repo, sub_parts);
+        }
+    }
+
+    /// Links the software to its module, returning their nodes.
+    /// The software itself is added with [`Self::add_software`].
+    fn link_software<'a>(
+        &mut self,
+        subject: &NamedNode,
+        repo: &Url,
+        software: &'a [v2::Software],
+    ) -> Vec<(NamedNode, &'a v2::Software)> {
+        software
+            .iter()
+            .enumerate()
+            .map(|(idx, sw)| {
+                let sw_node = module_node(repo, &format!("software-{idx}"));
+                self.add(subject, prop::SOFTWARE, sw_node.clone());
+                (sw_node, sw)
+            })
+            .collect()
+    }
+
+    fn add_software(&mut self, software: Vec<(NamedNode, &v2::Software)>) {
+        for (sw_node, sw) in software {
+            self.add_type(&sw_node, class::SOFTWARE);
+            if let Some(label) = &sw.label {
+                self.triples.push(Triple::new(
+                    sw_node.clone(),
+                    rdfs::LABEL,
+                    Literal::new_simple_literal(label),
+                ));
+            }
+            if let Some(release) = &sw.release {
+                self.add(&sw_node, prop::RELEASE, url_node(release));
+            }
+        }
+    }
+
+    fn to_turtle(&self) -> Result<String, SerError> {
+        log::debug!("Serializing to Turtle ...");
+        let mut serializer = TurtleSerializer::new();
+        for (prefix, namespace) in [
+            ("okh", NS_OKH),
+            ("odrl", NS_ODRL),
+            ("otrl", NS_OTRL),
+            ("rdfs", NS_RDFS),
+        ] {
+            serializer = serializer
+                .with_prefix(prefix, namespace)
+                .map_err(std::io::Error::other)?;
+        }
+        let mut writer = serializer.for_writer(Vec::new());
+        for triple in &self.triples {
+            writer.serialize_triple(triple)?;
+        }
+        let turtle = writer.finish()?;
+        Ok(String::from_utf8_lossy(&turtle).into_owned())
+    }
+}
+
+/// Reports what happens to each property of the OKH LOSH manifest.
+fn report(v2: &v2::Okh) -> Report {
+    let mut report = Report::new(v2::FORMAT_NAME, okh_rdf::FORMAT_NAME);
+    for (name, target, is_set) in [
+        ("okhv", prop::OKHV, true),
+        ("name", prop::NAME, true),
+        ("repo", prop::REPO, true),
+        ("version", prop::VERSION, true),
+        ("license", prop::LICENSE, true),
+        ("licensor", prop::LICENSOR, true),
+        (
+            "upload-method",
+            prop::UPLOAD_METHOD,
+            v2.upload_method.is_some(),
+        ),
+        (
+            "organisation",
+            prop::ORGANISATION,
+            v2.organisation.is_some(),
+        ),
+        ("readme", prop::README, v2.readme.is_some()),
+        (
+            "contribution-guide",
+            prop::CONTRIBUTION_GUIDE,
+            v2.contribution_guide.is_some(),
+        ),
+        ("image", prop::IMAGE, !v2.image.is_empty()),
+        (
+            "documentation-language",
+            prop::DOCUMENTATION_LANGUAGE,
+            v2.documentation_language.is_some(),
+        ),
+        ("attestation", prop::ATTESTATION, !v2.attestation.is_empty()),
+        (
+            "standard-compliance",
+            prop::STANDARD_COMPLIANCE,
+            !v2.standard_compliance.is_empty(),
+        ),
+        (
+            "cpc-patent-class",
+            prop::CPC_PATENT_CLASS,
+            v2.cpc_patent_class.is_some(),
+        ),
+        ("tsdc", prop::TSDC, v2.tsdc.is_some()),
+        ("bom", prop::BOM, v2.bom.is_some()),
+        ("release", prop::RELEASE, v2.release.is_some()),
+        ("timestamp", prop::TIMESTAMP, v2.timestamp.is_some()),
+        ("fork-of", prop::FORK_OF, v2.fork_of.is_some()),
+        ("function", prop::FUNCTION, v2.function.is_some()),
+        (
+            "documentation-readiness-level",
+            prop::ODRL,
+            v2.documentation_readiness_level.is_some(),
+        ),
+        (
+            "technology-readiness-level",
+            prop::OTRL,
+            v2.technology_readiness_level.is_some(),
+        ),
+        ("user-manual", prop::USER_MANUAL, v2.user_manual.is_some()),
+        (
+            "manufacturing-instructions",
+            prop::MANUFACTURING_INSTRUCTIONS,
+            !v2.manufacturing_instructions.is_empty(),
+        ),
+        ("software", prop::SOFTWARE, !v2.software.is_empty()),
+        ("standard", prop::STANDARD, !v2.standard.is_empty()),
+        ("source", prop::SOURCE, !v2.source.is_empty()),
+        ("export", prop::EXPORT, !v2.export.is_empty()),
+        ("auxiliary", prop::AUXILIARY, !v2.auxiliary.is_empty()),
+        ("part", prop::PART, !v2.part.is_empty()),
+    ] {
+        if is_set {
+            report.mapped(name, target);
+        }
+    }
+    if v2.license_aspects.is_some() {
+        report.dropped(okh_rdf::UNMAPPED_PROP, okh_rdf::UNMAPPED_NOTE);
+    }
+    report
+}
+
+/// Converts an OKH LOSH manifest to RDF, serialized as Turtle,
+/// using the OKH LOSH ontology.
+///
+/// The module itself gets the IRI `<repo>#module`,
+/// its parts and software get IRIs derived from that.
+/// Files get the IRI `<repo>/<path>`.
+///
+/// # Errors
+///
+/// If serializing to Turtle fails.
+pub fn convert(v2: &v2::Okh) -> Result<(String, Report), Error> {
+    log::debug!("Converting OKH v2 to RDF ...");
+    let report = report(v2);
+    let mut graph = Graph::default();
+    let module = module_node(&v2.repo, okh_rdf::MODULE_FRAGMENT);
+
+    graph.add_type(&module, class::MODULE);
+    graph.add_str(&module, prop::OKHV, &v2.okhv);
+    graph.add_str(&module, prop::NAME, &v2.name);
+    graph.add(&module, prop::REPO, url_node(&v2.repo));
+    graph.add_str(&module, prop::VERSION, &v2.version);
+    graph.add_str(&module, prop::LICENSE, &v2.license);
+    graph.add_str(&module, prop::LICENSOR, &v2.licensor);
+    graph.add_opt_str(&module, prop::UPLOAD_METHOD, v2.upload_method.as_ref());
+    graph.add_opt_str(&module, prop::ORGANISATION, v2.organisation.as_ref());
+    graph.add_paths(&module, prop::README, &v2.repo, &v2.readme);
+    graph.add_paths(
+        &module,
+        prop::CONTRIBUTION_GUIDE,
+        &v2.repo,
+        &v2.contribution_guide,
+    );
+    graph.add_paths(&module, prop::IMAGE, &v2.repo, &v2.image);
+    graph.add_opt_str(
+        &module,
+        prop::DOCUMENTATION_LANGUAGE,
+        v2.documentation_language.as_ref(),
+    );
+    for attestation in &v2.attestation {
+        graph.add(&module, prop::ATTESTATION, url_node(attestation));
+    }
+    for standard in &v2.standard_compliance {
+        graph.add_str(&module, prop::STANDARD_COMPLIANCE, standard);
+    }
+    graph.add_opt_str(
+        &module,
+        prop::CPC_PATENT_CLASS,
+        v2.cpc_patent_class.as_ref(),
+    );
+    graph.add_opt_str(&module, prop::TSDC, v2.tsdc.as_ref());
+    graph.add_paths(&module, prop::BOM, &v2.repo, &v2.bom);
+    if let Some(release) = &v2.release {
+        graph.add(&module, prop::RELEASE, url_node(release));
+    }
+    graph.add_opt_str(&module, prop::TIMESTAMP, v2.timestamp.as_ref());
+    if let Some(fork_of) = &v2.fork_of {
+        graph.add(&module, prop::FORK_OF, url_node(fork_of));
+    }
+    graph.add_opt_str(&module, prop::FUNCTION, v2.function.as_ref());
+    if let Some(odrl) = v2
+        .documentation_readiness_level
+        .as_ref()
+        .and_then(|odrl| oxrl_node(NS_ODRL, odrl))
+    {
+        graph.add(&module, prop::ODRL, odrl);
+    }
+    if let Some(otrl) = v2
+        .technology_readiness_level
+        .as_ref()
+        .and_then(|otrl| oxrl_node(NS_OTRL, otrl))
+    {
+        graph.add(&module, prop::OTRL, otrl);
+    }
+    graph.add_paths(&module, prop::USER_MANUAL, &v2.repo, &v2.user_manual);
+    graph.add_paths(
+        &module,
+        prop::MANUFACTURING_INSTRUCTIONS,
+        &v2.repo,
+        &v2.manufacturing_instructions,
+    );
+    let software = graph.link_software(&module, &v2.repo, &v2.software);
+    for standard in &v2.standard {
+        graph.add_str(&module, prop::STANDARD, standard);
+    }
+    graph.add_paths(&module, prop::SOURCE, &v2.repo, &v2.source);
+    graph.add_paths(&module, prop::EXPORT, &v2.repo, &v2.export);
+    graph.add_paths(&module, prop::AUXILIARY, &v2.repo, &v2.auxiliary);
+    let parts = graph.link_parts(&module, &v2.repo, "part", &v2.part);
+    // NOTE We add these only now, to keep all the module properties together.
+    graph.add_software(software);
+    graph.add_parts(&v2.repo, parts);
+
+    Ok((graph.to_turtle()?, report))
+}
+
+/// Converts an OKH LOSH manifest read from `input`,
+/// writing the RDF/Turtle to `output`.
+///
+/// # Errors
+///
+/// If reading, converting or writing fails.
+pub fn convert_io<R: Read, W: Write>(input: R, mut output: W) -> Result<Report, Error> {
+    let v2 = v2::Okh::from_toml_reader(input)?;
+    let (turtle, report) = convert(&v2)?;
+    log::debug!("Writing Turtle ...");
+    output
+        .write_all(turtle.as_bytes())
+        .map_err(SerError::from)?;
+    Ok(report)
+}
@@ -0,0 +1,245 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::io::{Read, Write};
+
+use crate::formats::Locator;
+use crate::formats::v1;
+use crate::formats::v2;
+
+use super::Error;
+use super::report::Report;
+
+/// The OKH v1 manifest version we produce.
+const V1_MANIFEST_VERSION: &str = "1.0.0";
+
+fn licensor(v2: &v2::Okh) -> v1::Person {
+    let (name, email) = v2.licensor_name_and_email();
+    v1::Person {
+        name: name.map(ToOwned::to_owned),
+        email: email.map(ToOwned::to_owned),
+        ..Default::default()
+    }
+}
+
+fn license(v2: &v2::Okh) -> v1::License {
+    if let Some(aspects) = &v2.license_aspects {
+        return v1::License {
+            hardware: aspects.hardware.clone(),
+            documentation: aspects.documentation.clone(),
+            software: aspects.software.clone(),
+        };
+    }
+    // NOTE OKH LOSH has only a single license for the whole project,
+    //      so we use it for both the hardware and its documentation.
+    v1::License {
+        hardware: Some(v2.license.clone()),
+        documentation: Some(v2.license.clone()),
+        software: None,
+    }
+}
+
+fn software(v2: &v2::Okh) -> Vec<v1::Document> {
+    v2.software
+        .iter()
+        .map(|sw| v1::Document {
+            title: sw.label.clone(),
+            path: sw.release.clone().map(Locator::Url),
+        })
+        .collect()
+}
+
+fn documents(paths: &[relative_path::RelativePathBuf]) -> Vec<v1::Document> {
+    paths
+        .iter()
+        .map(|path| v1::Document {
+            title: None,
+            path: Some(Locator::Path(path.clone())),
+        })
+        .collect()
+}
+
+/// Reports what happens to the license(s) of the OKH LOSH manifest.
+fn report_license(v2: &v2::Okh, report: &mut Report) {
+    if let Some(aspects) = &v2.license_aspects {
+        report.dropped("license", "split up by 'license-aspects'");
+        for (name, target, is_set) in [
+            (
+                "license-aspects.hardware",
+                "license.hardware",
+                aspects.hardware.is_some(),
+            ),
+            (
+                "license-aspects.documentation",
+                "license.documentation",
+                aspects.documentation.is_some(),
+            ),
+            (
+                "license-aspects.software",
+                "license.software",
+                aspects.software.is_some(),
+            ),
+        ] {
+            if is_set {
+                report.mapped(name, target);
+            }
+        }
+    } else {
+        report.mapped("license", "license.hardware");
+    }
+}
+
+/// Reports what happens to each property of the OKH LOSH manifest.
+fn report(v2: &v2::Okh) -> Report {
+    let mut report = Report::new(v2::FORMAT_NAME, v1::FORMAT_NAME);
+    let note = "not supported by OKH v1";
+    report.mapped("okhv", "okh-manifest-version");
+    report.mapped("name", "title");
+    report.mapped("repo", "documentation-home");
+    report.mapped("version", "version");
+    report_license(v2, &mut report);
+    report.mapped("licensor", "licensor");
+    for (name, target, is_set) in [
+        (
+            "documentation-language",
+            "documentation-language",
+            v2.documentation_language.is_some(),
+        ),
+        ("bom", "bom", v2.bom.is_some()),
+        ("release", "archive-download", v2.release.is_some()),
+        ("timestamp", "date-updated", v2.timestamp.is_some()),
+        ("fork-of", "derivative-of", v2.fork_of.is_some()),
+        ("function", "description", v2.function.is_some()),
+        (
+            "user-manual",
+            "operating-instructions",
+            v2.user_manual.is_some(),
+        ),
+    ] {
+        if is_set {
+            report.mapped(name, target);
+        }
+    }
+    for (name, is_set) in [
+        ("upload-method", v2.upload_method.is_some()),
+        ("organisation", v2.organisation.is_some()),
+        ("readme", v2.readme.is_some()),
+        ("contribution-guide", v2.contribution_guide.is_some()),
+        ("attestation", !v2.attestation.is_empty()),
+        ("cpc-patent-class", v2.cpc_patent_class.is_some()),
+        ("tsdc", v2.tsdc.is_some()),
+        (
+            "documentation-readiness-level",
+            v2.documentation_readiness_level.is_some(),
+        ),
+        (
+            "technology-readiness-level",
+            v2.technology_readiness_level.is_some(),
+        ),
+        ("standard", !v2.standard.is_empty()),
+        ("source", !v2.source.is_empty()),
+        ("export", !v2.export.is_empty()),
+        ("auxiliary", !v2.auxiliary.is_empty()),
+    ] {
+        if is_set {
+            report.dropped(name, note);
+        }
+    }
+    for idx in 0..v2.image.len() {
+        if idx == 0 {
+            report.mapped("image[0]", "image");
+        } else {
+            report.dropped(
+                format!("image[{idx}]"),
+                "OKH v1 supports only a single image",
+            );
+        }
+    }
+    for idx in 0..v2.manufacturing_instructions.len() {
+        report.mapped(
+            format!("manufacturing-instructions[{idx}]"),
+            "making-instructions",
+        );
+    }
+    for idx in 0..v2.software.len() {
+        report.mapped(format!("software[{idx}]"), "software");
+    }
+    for idx in 0..v2.standard_compliance.len() {
+        report.mapped(format!("standard-compliance[{idx}]"), "standards-used");
+    }
+    // NOTE OKH v1 sub-parts need a manifest or web page of their own,
+    //      which OKH LOSH parts do not have.
+    for idx in 0..v2.part.len() {
+        report.dropped(
+            format!("part[{idx}]"),
+            "OKH v1 sub-parts require a 'manifest' or 'web' URL of their own",
+        );
+    }
+    report
+}
+
+/// Converts an OKH LOSH manifest to an OKH v1 one.
+///
+/// As OKH v1 knows fewer properties than OKH LOSH,
+/// this conversion is lossy.
+/// Next to the converted manifest,
+/// this returns a report of what happened to each OKH LOSH property.
+pub fn convert(v2: v2::Okh) -> (v1::Okh, Report) {
+    log::debug!("Converting OKH v2 to v1 ...");
+    let report = report(&v2);
+    let license = license(&v2);
+    let licensor = Some(licensor(&v2));
+    let software = software(&v2);
+    let making_instructions = documents(&v2.manufacturing_instructions);
+    let operating_instructions = documents(v2.user_manual.as_slice());
+    let standards_used = v2
+        .standard_compliance
+        .into_iter()
+        .map(|standard_title| v1::Standard {
+            reference: String::new(),
+            standard_title,
+        })
+        .collect();
+    let derivative_of = v2.fork_of.map(|fork_of| v1::OtherThing {
+        title: None,
+        manifest: None,
+        web: Some(fork_of),
+    });
+
+    let v1 = v1::Okh {
+        title: v2.name,
+        description: v2.function.unwrap_or_default(),
+        project_link: Some(v2.repo.clone()),
+        documentation_home: Some(v2.repo),
+        image: v2.image.into_iter().next(),
+        license,
+        licensor,
+        manifest_version: Some(V1_MANIFEST_VERSION.to_owned()),
+        date_updated: v2.timestamp,
+        version: Some(v2.version),
+        archive_download: v2.release,
+        documentation_language: v2.documentation_language,
+        bom: v2.bom,
+        making_instructions,
+        operating_instructions,
+        software,
+        standards_used,
+        derivative_of,
+        ..Default::default()
+    };
+    (v1, report)
+}
+
+/// Converts an OKH LOSH manifest read from `input`,
+/// writing the OKH v1 manifest to `output`.
+///
+/// # Errors
+///
+/// If reading, parsing or writing fails.
+pub fn convert_io<R: Read, W: Write>(input: R, output: W) -> Result<Report, Error> {
+    let v2 = v2::Okh::from_toml_reader(input)?;
+    let (v1, report) = convert(v2);
+    v1.to_yaml_writer(output)?;
+    Ok(report)
+}
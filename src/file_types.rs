@@ -3,3 +3,11 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 include!(concat!(env!("OUT_DIR"), "/file_types.rs"));
+
+/// Looks up the format of a file by its extension
+/// in all the known file type categories (CAD and PCB).
+pub fn find_by_ext(ext: &str) -> Option<&'static FileFormat<&'static str>> {
+    CAD.iter()
+        .chain(PCB.iter())
+        .find(|format| format.extension.eq_ignore_ascii_case(ext))
+}
@@ -0,0 +1,170 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Software citation meta-data formats,
+//! namely the [Citation File Format](https://citation-file-format.github.io/)
+//! and [CodeMeta](https://codemeta.github.io/).
+
+use std::{fs, io::Write, path::Path};
+
+use regex::Regex;
+use serde::Serialize;
+use url::Url;
+
+use crate::macros::rgx;
+
+use super::SerError;
+
+type DSString = String;
+
+pub const FORMAT_NAME_CFF: &str = "CFF";
+pub const FORMAT_NAME_CODEMETA: &str = "CodeMeta";
+pub const CFF_FILE_NAME: &str = "CITATION.cff";
+pub const CODEMETA_FILE_NAME: &str = "codemeta.json";
+
+/// The CFF version we produce.
+pub const CFF_VERSION: &str = "1.2.0";
+pub const CODEMETA_CONTEXT: &str = "https://w3id.org/codemeta/3.0";
+
+/// A CFF entity, which may be a person or an organisation.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CffEntity {
+    pub name: DSString,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<DSString>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Cff {
+    #[serde(rename = "cff-version")]
+    pub format_version: DSString,
+
+    pub message: DSString,
+
+    pub title: DSString,
+
+    pub version: DSString,
+
+    /// SPDX license IDs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub license: Vec<DSString>,
+
+    pub authors: Vec<CffEntity>,
+
+    pub repository_code: Url,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository_artifact: Option<Url>,
+
+    /// Date in the format `YYYY-MM-DD`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_released: Option<DSString>,
+}
+
+impl Cff {
+    pub fn to_yaml(&self) -> Result<String, SerError> {
+        log::debug!("Serializing to CFF (YAML) ...");
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    pub fn to_yaml_writer<W: Write>(&self, mut writer: W) -> Result<(), SerError> {
+        let yaml_str = self.to_yaml()?;
+        log::debug!("Writing CFF ...");
+        writer.write_all(yaml_str.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn to_yaml_file<OP>(&self, cff_file: OP) -> Result<(), SerError>
+    where
+        OP: AsRef<Path>,
+    {
+        let yaml_str = self.to_yaml()?;
+        log::debug!("Writing to CFF file ...");
+        fs::write(cff_file, yaml_str)?;
+        Ok(())
+    }
+
+    pub fn ext_matcher() -> &'static Regex {
+        rgx!(r"(^|\.)[cC][fF][fF]$")
+    }
+
+    pub fn file_matcher() -> &'static Regex {
+        rgx!(r"(^|/)CITATION\.cff$")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeMetaPerson {
+    #[serde(rename = "@type")]
+    pub kind: DSString,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<DSString>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<DSString>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeMeta {
+    #[serde(rename = "@context")]
+    pub context: DSString,
+
+    #[serde(rename = "@type")]
+    pub kind: DSString,
+
+    pub name: DSString,
+
+    pub version: DSString,
+
+    /// SPDX license URLs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub license: Vec<Url>,
+
+    pub author: Vec<CodeMetaPerson>,
+
+    pub code_repository: Url,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<Url>,
+
+    /// Date in the format `YYYY-MM-DD`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_modified: Option<DSString>,
+}
+
+impl CodeMeta {
+    pub fn to_json(&self) -> Result<String, SerError> {
+        log::debug!("Serializing to CodeMeta (JSON) ...");
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn to_json_writer<W: Write>(&self, mut writer: W) -> Result<(), SerError> {
+        let json_str = self.to_json()?;
+        log::debug!("Writing CodeMeta ...");
+        writer.write_all(json_str.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn to_json_file<OP>(&self, json_file: OP) -> Result<(), SerError>
+    where
+        OP: AsRef<Path>,
+    {
+        let json_str = self.to_json()?;
+        log::debug!("Writing to CodeMeta file ...");
+        fs::write(json_file, json_str)?;
+        Ok(())
+    }
+
+    /// `CodeMeta` files are only recognized by their name,
+    /// as they share their extension with other formats.
+    pub fn file_matcher() -> &'static Regex {
+        rgx!(r"(^|/)codemeta\.json$")
+    }
+}
@@ -8,6 +8,11 @@ use serde::ser::{Serialize, Serializer};
 use std::fmt;
 use url::Url;
 
+pub mod citation;
+pub mod oshwa;
+pub mod rdf;
+pub mod spans;
+pub mod toml_update;
 pub mod v1;
 pub mod v2;
 
@@ -21,6 +26,17 @@ pub enum ParseError {
 
     #[error("Failed to parse YAML")]
     Yaml(#[from] serde_yaml::Error),
+
+    #[error("Failed to parse JSON")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Failed to parse Turtle")]
+    Turtle(#[from] oxttl::TurtleParseError),
+
+    #[error(
+        "At least one of the three licenses (documentation, hardware or software) has to be set"
+    )]
+    NoLicense,
 }
 
 /// Serialization Error
@@ -32,8 +48,14 @@ pub enum SerError {
     #[error("Failed to serialize TOML")]
     Toml(#[from] toml::ser::Error),
 
+    #[error("Failed to parse the new TOML document to update the existing one with")]
+    TomlUpdate(#[source] Box<dyn std::error::Error + Send + Sync>),
+
     #[error("Failed to serialize YAML")]
     Yaml(#[from] serde_yaml::Error),
+
+    #[error("Failed to serialize JSON")]
+    Json(#[from] serde_json::Error),
 }
 
 /// A (serde compatible) property type,
@@ -0,0 +1,126 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! OSHWA certification records,
+//! as delivered by the [OSHWA certification API](https://certificationapi.oshwa.org/).
+
+use std::{fs, io::Read, path::Path};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::macros::rgx;
+
+use super::ParseError;
+
+type DSString = String;
+
+pub const FORMAT_NAME: &str = "OSHWA-certification";
+
+/// Base URL of the public, human oriented certification pages
+pub const CERTIFICATION_PAGE_BASE_URL: &str = "https://certification.oshwa.org/";
+
+/// License values OSHWA uses to denote that no (standard) license applies
+const NON_LICENSES: &[&str] = &["", "None", "Other"];
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Certification {
+    /// The certification ID, e.g. "US000001"
+    pub oshwa_uid: DSString,
+
+    pub responsible_party: DSString,
+
+    #[serde(default)]
+    pub country: Option<DSString>,
+
+    #[serde(default)]
+    pub public_contact: Option<DSString>,
+
+    pub project_name: DSString,
+
+    #[serde(default)]
+    pub project_website: Option<DSString>,
+
+    #[serde(default)]
+    pub project_version: Option<DSString>,
+
+    #[serde(default)]
+    pub project_description: Option<DSString>,
+
+    /// The main OSHWA category
+    #[serde(default)]
+    pub primary_type: Option<DSString>,
+
+    /// Further OSHWA categories
+    #[serde(default)]
+    pub additional_type: Vec<DSString>,
+
+    #[serde(default)]
+    pub project_keywords: Vec<DSString>,
+
+    #[serde(default)]
+    pub documentation_url: Option<DSString>,
+
+    #[serde(default)]
+    pub hardware_license: Option<DSString>,
+
+    #[serde(default)]
+    pub software_license: Option<DSString>,
+
+    #[serde(default)]
+    pub documentation_license: Option<DSString>,
+
+    #[serde(default)]
+    #[serde(rename = "certificationDate")]
+    pub date: Option<DSString>,
+}
+
+impl Certification {
+    pub fn from_json(json_str: &str) -> Result<Self, ParseError> {
+        log::debug!("Parsing OSHWA certification JSON ...");
+        Ok(serde_json::from_str::<Self>(json_str)?)
+    }
+
+    pub fn from_json_reader<R: Read>(mut reader: R) -> Result<Self, ParseError> {
+        log::debug!("Reading OSHWA certification JSON to string ...");
+        let mut json_str = String::new();
+        reader.read_to_string(&mut json_str)?;
+
+        Self::from_json(&json_str)
+    }
+
+    pub fn from_json_file<IP>(json_file: IP) -> Result<Self, ParseError>
+    where
+        IP: AsRef<Path>,
+    {
+        log::debug!("Reading OSHWA certification JSON file to string ...");
+        let json_str = fs::read_to_string(json_file)?;
+
+        Self::from_json(&json_str)
+    }
+
+    /// The URL of the public certification page of this project
+    pub fn certification_page_url(&self) -> String {
+        format!(
+            "{CERTIFICATION_PAGE_BASE_URL}{}.html",
+            self.oshwa_uid.trim().to_lowercase()
+        )
+    }
+
+    /// Whether the given license value denotes an actual license
+    pub fn is_license(license: &str) -> bool {
+        !NON_LICENSES.contains(&license.trim())
+    }
+
+    pub fn ext_matcher() -> &'static Regex {
+        rgx!(r"(^|\.)[jJ][sS][oO][nN]$")
+    }
+
+    /// As certification records are usually named after their ID,
+    /// all JSON files are considered.
+    pub fn file_matcher() -> &'static Regex {
+        rgx!(r"\.[jJ][sS][oO][nN]$")
+    }
+}
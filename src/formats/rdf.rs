@@ -0,0 +1,236 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! The RDF representation of OKH LOSH manifests,
+//! as used by the LOSH graph database.
+
+use oxrdf::NamedNode;
+use percent_encoding::percent_decode_str;
+use regex::Regex;
+use relative_path::{RelativePath, RelativePathBuf};
+use serde::Serialize;
+use url::Url;
+
+use crate::macros::rgx;
+use crate::oxrl::{Odrl, Otrl};
+
+pub const FORMAT_NAME: &str = "OKH-LOSH-RDF";
+pub const MANIFEST_FILE_NAME: &str = "okh.ttl";
+/// The fragment of the repo URL that makes up the IRI of the module itself
+pub const MODULE_FRAGMENT: &str = "module";
+
+/// The OKH LOSH ontology
+pub const NS_OKH: &str = "http://w3id.org/oseg/ont/okh#";
+/// The Open Documentation Readiness Level individuals
+pub const NS_ODRL: &str = "http://w3id.org/oseg/ont/odrl#";
+/// The Open Technology Readiness Level individuals
+pub const NS_OTRL: &str = "http://w3id.org/oseg/ont/otrl#";
+pub const NS_RDFS: &str = "http://www.w3.org/2000/01/rdf-schema#";
+
+/// Local names (within [`NS_OKH`]) of the classes we use
+pub mod class {
+    pub const MODULE: &str = "Module";
+    pub const SOFTWARE: &str = "Software";
+}
+
+/// Local names (within [`NS_OKH`]) of the properties we use
+pub mod prop {
+    pub const OKHV: &str = "okhv";
+    pub const NAME: &str = "name";
+    pub const REPO: &str = "repo";
+    pub const VERSION: &str = "version";
+    pub const LICENSE: &str = "spdxLicense";
+    pub const LICENSOR: &str = "licensor";
+    pub const UPLOAD_METHOD: &str = "uploadMethod";
+    pub const ORGANISATION: &str = "organization";
+    pub const README: &str = "hasReadme";
+    pub const CONTRIBUTION_GUIDE: &str = "hasContributionGuide";
+    pub const IMAGE: &str = "hasImage";
+    pub const DOCUMENTATION_LANGUAGE: &str = "documentationLanguage";
+    pub const ATTESTATION: &str = "attestation";
+    pub const STANDARD_COMPLIANCE: &str = "standardCompliance";
+    pub const CPC_PATENT_CLASS: &str = "cpcPatentClass";
+    pub const TSDC: &str = "tsdc";
+    pub const BOM: &str = "hasBoM";
+    pub const RELEASE: &str = "release";
+    pub const TIMESTAMP: &str = "timestamp";
+    pub const FORK_OF: &str = "forkOf";
+    pub const FUNCTION: &str = "function";
+    pub const ODRL: &str = "documentationReadinessLevel";
+    pub const OTRL: &str = "technologyReadinessLevel";
+    pub const USER_MANUAL: &str = "hasUserManual";
+    pub const MANUFACTURING_INSTRUCTIONS: &str = "hasManufacturingInstructions";
+    pub const SOFTWARE: &str = "hasSoftware";
+    pub const STANDARD: &str = "standard";
+    pub const SOURCE: &str = "hasSource";
+    pub const EXPORT: &str = "hasExport";
+    pub const AUXILIARY: &str = "hasAuxiliary";
+    pub const PART: &str = "hasComponent";
+}
+
+/// The OKH LOSH ontology properties of a module,
+/// and the corresponding OKH LOSH (TOML) property.
+pub const PROPS: &[(&str, &str)] = &[
+    (prop::OKHV, "okhv"),
+    (prop::NAME, "name"),
+    (prop::REPO, "repo"),
+    (prop::VERSION, "version"),
+    (prop::LICENSE, "license"),
+    (prop::LICENSOR, "licensor"),
+    (prop::UPLOAD_METHOD, "upload-method"),
+    (prop::ORGANISATION, "organisation"),
+    (prop::README, "readme"),
+    (prop::CONTRIBUTION_GUIDE, "contribution-guide"),
+    (prop::IMAGE, "image"),
+    (prop::DOCUMENTATION_LANGUAGE, "documentation-language"),
+    (prop::ATTESTATION, "attestation"),
+    (prop::STANDARD_COMPLIANCE, "standard-compliance"),
+    (prop::CPC_PATENT_CLASS, "cpc-patent-class"),
+    (prop::TSDC, "tsdc"),
+    (prop::BOM, "bom"),
+    (prop::RELEASE, "release"),
+    (prop::TIMESTAMP, "timestamp"),
+    (prop::FORK_OF, "fork-of"),
+    (prop::FUNCTION, "function"),
+    (prop::ODRL, "documentation-readiness-level"),
+    (prop::OTRL, "technology-readiness-level"),
+    (prop::USER_MANUAL, "user-manual"),
+    (
+        prop::MANUFACTURING_INSTRUCTIONS,
+        "manufacturing-instructions",
+    ),
+    (prop::SOFTWARE, "software"),
+    (prop::STANDARD, "standard"),
+    (prop::SOURCE, "source"),
+    (prop::EXPORT, "export"),
+    (prop::AUXILIARY, "auxiliary"),
+    (prop::PART, "part"),
+];
+
+/// The OKH LOSH (TOML) property that has no counterpart in the ontology,
+/// and is thus lost in RDF and JSON-LD
+pub const UNMAPPED_PROP: &str = "license-aspects";
+/// Why [`UNMAPPED_PROP`] is lost in RDF and JSON-LD
+pub const UNMAPPED_NOTE: &str = "not part of the OKH LOSH ontology";
+
+/// The OKH LOSH (TOML) properties whose values are IRIs in RDF
+pub const IRI_VALUED_PROPS: &[&str] = &["repo", "attestation", "release", "fork-of"];
+/// The OKH LOSH (TOML) properties whose values are repo relative file paths,
+/// which are file IRIs in RDF (see [`file_iri`])
+pub const PATH_VALUED_PROPS: &[&str] = &[
+    "readme",
+    "contribution-guide",
+    "image",
+    "bom",
+    "user-manual",
+    "manufacturing-instructions",
+    "source",
+    "export",
+    "auxiliary",
+];
+/// The OKH LOSH (TOML) properties whose values are ODRL or OTRL individuals in RDF
+pub const OXRL_VALUED_PROPS: &[&str] = &[
+    "documentation-readiness-level",
+    "technology-readiness-level",
+];
+
+/// Creates the IRI of a class or property of the OKH LOSH ontology.
+pub fn okh_node(local_name: &str) -> NamedNode {
+    NamedNode::new_unchecked(format!("{NS_OKH}{local_name}"))
+}
+
+/// Creates the IRI of a (sub-)module or software,
+/// by setting a fragment on the repo URL.
+pub fn module_iri(repo: &Url, fragment: &str) -> Url {
+    let mut module_url = repo.clone();
+    module_url.set_fragment(Some(fragment));
+    module_url
+}
+
+/// The IRI the files of a repo are relative to,
+/// which is the repo URL with a trailing slash.
+pub fn files_base(repo: &Url) -> Url {
+    let mut base = repo.clone();
+    base.set_query(None);
+    base.set_fragment(None);
+    if !base.path().ends_with('/') {
+        let path = format!("{}/", base.path());
+        base.set_path(&path);
+    }
+    base
+}
+
+/// Creates the IRI of a file within the repo,
+/// by resolving its path against [`files_base`].
+/// Returns `None` if the path leads out of the repo.
+pub fn file_iri(repo: &Url, path: &RelativePath) -> Option<Url> {
+    let base = files_base(repo);
+    // NOTE The "./" prevents paths like "a:b" from being taken for absolute IRIs.
+    base.join(&format!("./{path}"))
+        .ok()
+        .filter(|iri| iri.as_str().starts_with(base.as_str()))
+}
+
+/// Extracts the repo relative path from a file IRI,
+/// the inverse of [`file_iri`].
+/// Returns `None` if the IRI does not lie within the repo.
+pub fn file_path(repo: &Url, iri: &str) -> Option<RelativePathBuf> {
+    let base = files_base(repo);
+    iri.strip_prefix(base.as_str())
+        .and_then(|encoded| percent_decode_str(encoded).decode_utf8().ok())
+        .map(|path| RelativePathBuf::from(path.as_ref()))
+}
+
+/// Adds a JSON-LD term for each ODRL or OTRL level,
+/// named like in the TOML serialization (e.g. `OTRL-4`).
+fn insert_levels<T: TryFrom<u8> + Serialize>(
+    context: &mut serde_json::Map<String, serde_json::Value>,
+    prefix: &str,
+) {
+    for level in (1..).map_while(|num| T::try_from(num).ok()) {
+        if let Ok(serde_json::Value::String(name)) = serde_json::to_value(level) {
+            let iri = format!("{prefix}:{name}");
+            context.insert(name, iri.into());
+        }
+    }
+}
+
+/// Creates a JSON-LD context,
+/// mapping the OKH LOSH (TOML/JSON) properties to the OKH LOSH ontology.
+/// File paths are resolved against the [`files_base`] of `repo`,
+/// yielding the same IRIs as [`file_iri`].
+pub fn jsonld_context(repo: &Url) -> serde_json::Value {
+    let mut context = serde_json::Map::new();
+    context.insert("@base".to_owned(), files_base(repo).as_str().into());
+    context.insert("okh".to_owned(), NS_OKH.into());
+    context.insert("rdfs".to_owned(), NS_RDFS.into());
+    context.insert("odrl".to_owned(), NS_ODRL.into());
+    context.insert("otrl".to_owned(), NS_OTRL.into());
+    for (local_name, key) in PROPS {
+        let iri = format!("okh:{local_name}");
+        let term = if IRI_VALUED_PROPS.contains(key) || PATH_VALUED_PROPS.contains(key) {
+            serde_json::json!({ "@id": iri, "@type": "@id" })
+        } else if OXRL_VALUED_PROPS.contains(key) {
+            serde_json::json!({ "@id": iri, "@type": "@vocab" })
+        } else {
+            iri.into()
+        };
+        context.insert((*key).to_owned(), term);
+    }
+    // ODRL and OTRL levels (e.g. `OTRL-4`),
+    // which expand to their individuals through `"@type": "@vocab"`
+    insert_levels::<Odrl>(&mut context, "odrl");
+    insert_levels::<Otrl>(&mut context, "otrl");
+    // Software
+    context.insert("label".to_owned(), "rdfs:label".into());
+    serde_json::Value::Object(context)
+}
+
+pub fn ext_matcher() -> &'static Regex {
+    rgx!(r"(^|\.)[tT][tT][lL]$")
+}
+
+pub fn file_matcher() -> &'static Regex {
+    rgx!(r"okh\.[tT][tT][lL]$")
+}
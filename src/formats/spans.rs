@@ -0,0 +1,426 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Finds out where in a manifest file (YAML or TOML) each value is located,
+//! which serde does not tell us.
+
+use std::{collections::HashMap, fmt};
+
+use toml_edit::{Item, Key, Table, Value};
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::Marker;
+
+/// A position within a text file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+pub struct Location {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column number, counted in characters
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+impl From<Marker> for Location {
+    fn from(marker: Marker) -> Self {
+        Self {
+            line: marker.line(),
+            column: marker.col() + 1,
+        }
+    }
+}
+
+/// Maps the path of each value in a document to where it starts.
+/// The paths use the same notation as the conversion report,
+/// e.g. `licensor.name` or `operating-instructions[1]`;
+/// the root is the empty path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Spans {
+    starts: HashMap<String, Location>,
+}
+
+impl Spans {
+    /// Indexes the first document of a YAML file.
+    /// If the YAML is broken, only the part before the error is indexed.
+    pub fn from_yaml(yaml_str: &str) -> Self {
+        let mut indexer = YamlIndexer::default();
+        if let Err(err) = Parser::new(yaml_str.chars()).load(&mut indexer, false) {
+            log::debug!("Indexing the YAML value locations stopped early: {err}");
+        }
+        indexer.spans
+    }
+
+    /// Indexes a TOML file.
+    /// If the TOML is broken, nothing is indexed.
+    pub fn from_toml(toml_str: &str) -> Self {
+        let mut indexer = TomlIndexer::new(toml_str);
+        match toml_edit::Document::parse(toml_str) {
+            Ok(doc) => {
+                // NOTE The root table has no span of its own,
+                //      so we use where its first key starts.
+                if let Some(start) = indexer.table("", doc.as_table()) {
+                    indexer.spans.starts.insert(String::new(), start);
+                }
+            }
+            Err(err) => log::debug!("Indexing the TOML value locations failed: {err}"),
+        }
+        indexer.spans
+    }
+
+    /// Where the value at `path` starts, if it exists.
+    pub fn get(&self, path: &str) -> Option<Location> {
+        self.starts.get(path).copied()
+    }
+
+    /// Where the value at `path` starts;
+    /// or if it does not exist, where its closest existing ancestor starts.
+    pub fn get_closest(&self, path: &str) -> Option<Location> {
+        let mut cur = path;
+        loop {
+            if let Some(location) = self.get(cur) {
+                return Some(location);
+            }
+            let parent_end = cur.rfind(['.', '['])?;
+            cur = cur.get(..parent_end)?;
+        }
+    }
+}
+
+/// Joins the path of a mapping and one of its keys.
+pub fn join_key(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+/// Joins the path of a sequence and one of its indices.
+pub fn join_index(parent: &str, index: usize) -> String {
+    format!("{parent}[{index}]")
+}
+
+/// Converts a path in the notation used here
+/// (e.g. `part[0].source[1]`)
+/// into a JSON pointer (e.g. `/part/0/source/1`).
+pub fn to_json_pointer(path: &str) -> String {
+    let mut pointer = String::new();
+    for key_part in path.split('.').filter(|key_part| !key_part.is_empty()) {
+        let mut pieces = key_part.split('[');
+        if let Some(key) = pieces.next().filter(|key| !key.is_empty()) {
+            pointer.push('/');
+            pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+        }
+        for index in pieces {
+            pointer.push('/');
+            pointer.push_str(index.trim_end_matches(']'));
+        }
+    }
+    pointer
+}
+
+/// Converts a JSON pointer (e.g. `/part/0/source/1`)
+/// into a path in the notation used here (e.g. `part[0].source[1]`).
+/// Numeric segments are taken to be indices,
+/// as OKH has no numeric keys.
+pub fn from_json_pointer(pointer: &str) -> String {
+    pointer
+        .split('/')
+        .skip(1)
+        .fold(String::new(), |path, segment| {
+            let key = segment.replace("~1", "/").replace("~0", "~");
+            key.parse::<usize>()
+                .map_or_else(|_| join_key(&path, &key), |index| join_index(&path, index))
+        })
+}
+
+/// Renders the line of `text` at `location`,
+/// with `label` pointing at its column, for example:
+///
+/// ```text
+///   |
+/// 4 | version = 3
+///   |           ^ 3 is not of type "string"
+/// ```
+pub fn annotate(text: &str, location: Location, label: &str) -> String {
+    let line = text
+        .lines()
+        .nth(location.line.saturating_sub(1))
+        .unwrap_or_default();
+    let line_num = location.line.to_string();
+    let gutter = " ".repeat(line_num.len());
+    // NOTE We keep tabs, so the marker lines up with the text.
+    let indent: String = line
+        .chars()
+        .take(location.column.saturating_sub(1))
+        .map(|chr| if chr == '\t' { '\t' } else { ' ' })
+        .collect();
+    format!("{gutter} |\n{line_num} | {line}\n{gutter} | {indent}^ {label}")
+}
+
+/// Maps byte offsets within a text to locations.
+#[derive(Debug)]
+struct LineIndex<'a> {
+    text: &'a str,
+    /// The byte offset of the start of each line
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    fn location(&self, offset: usize) -> Location {
+        let line = self
+            .line_starts
+            .partition_point(|line_start| *line_start <= offset);
+        let line_start = self
+            .line_starts
+            .get(line.saturating_sub(1))
+            .copied()
+            .unwrap_or_default();
+        let column = self
+            .text
+            .get(line_start..offset)
+            .map_or(0, |before| before.chars().count());
+        Location {
+            line,
+            column: column + 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TomlIndexer<'a> {
+    lines: LineIndex<'a>,
+    spans: Spans,
+}
+
+impl<'a> TomlIndexer<'a> {
+    fn new(toml_str: &'a str) -> Self {
+        Self {
+            lines: LineIndex::new(toml_str),
+            spans: Spans::default(),
+        }
+    }
+
+    /// Indexes the values of a table,
+    /// and returns where its first key starts.
+    fn table(&mut self, path: &str, table: &Table) -> Option<Location> {
+        table
+            .iter()
+            .filter_map(|(key, item)| {
+                let item_start = self.item(&join_key(path, key), item);
+                table
+                    .key(key)
+                    .and_then(Key::span)
+                    .map(|span| self.lines.location(span.start))
+                    .or(item_start)
+            })
+            .min()
+    }
+
+    /// Indexes an item and its children,
+    /// and returns where it starts.
+    /// Tables without a header (e.g. the items of an array of tables)
+    /// start where their first key starts.
+    fn item(&mut self, path: &str, item: &Item) -> Option<Location> {
+        let start = match item {
+            Item::None => None,
+            Item::Value(value) => return self.value(path, value),
+            Item::Table(table) => {
+                let first = self.table(path, table);
+                item.span()
+                    .map(|span| self.lines.location(span.start))
+                    .or(first)
+            }
+            Item::ArrayOfTables(tables) => {
+                let mut first = None;
+                for (index, table) in tables.iter().enumerate() {
+                    let table_path = join_index(path, index);
+                    if let Some(table_start) = self.table(&table_path, table) {
+                        self.spans.starts.insert(table_path, table_start);
+                        first = first.or(Some(table_start));
+                    }
+                }
+                first
+            }
+        };
+        if let Some(location) = start {
+            self.spans.starts.insert(path.to_owned(), location);
+        }
+        start
+    }
+
+    /// Indexes a value and its children,
+    /// and returns where it starts.
+    fn value(&mut self, path: &str, value: &Value) -> Option<Location> {
+        let start = value.span().map(|span| self.lines.location(span.start));
+        if let Some(location) = start {
+            self.spans.starts.insert(path.to_owned(), location);
+        }
+        match value {
+            Value::Array(array) => {
+                for (index, element) in array.iter().enumerate() {
+                    self.value(&join_index(path, index), element);
+                }
+            }
+            Value::InlineTable(table) => {
+                for (key, element) in table {
+                    self.value(&join_key(path, key), element);
+                }
+            }
+            Value::String(_)
+            | Value::Integer(_)
+            | Value::Float(_)
+            | Value::Boolean(_)
+            | Value::Datetime(_) => (),
+        }
+        start
+    }
+}
+
+#[derive(Debug)]
+enum Container {
+    Mapping {
+        /// The last key read, if we are now at its value
+        key: Option<String>,
+        /// Whether no key was read yet
+        empty: bool,
+    },
+    Sequence {
+        next_index: usize,
+    },
+}
+
+#[derive(Debug)]
+struct Frame {
+    path: String,
+    container: Container,
+}
+
+#[derive(Debug, Default)]
+struct YamlIndexer {
+    spans: Spans,
+    stack: Vec<Frame>,
+}
+
+impl YamlIndexer {
+    /// Returns the path of the node that starts now,
+    /// or `None` if it is a mapping key.
+    fn node_path(&self) -> Option<String> {
+        match self.stack.last() {
+            None => Some(String::new()),
+            Some(Frame {
+                path,
+                container: Container::Mapping { key: Some(key), .. },
+            }) => Some(join_key(path, key)),
+            Some(Frame {
+                container: Container::Mapping { key: None, .. },
+                ..
+            }) => None,
+            Some(Frame {
+                path,
+                container: Container::Sequence { next_index },
+            }) => Some(join_index(path, *next_index)),
+        }
+    }
+
+    /// Moves on to the next key or item of the enclosing container.
+    fn node_done(&mut self, scalar_key: Option<String>) {
+        if let Some(frame) = self.stack.last_mut() {
+            match &mut frame.container {
+                Container::Mapping { key, .. } => {
+                    *key = if key.is_some() {
+                        None
+                    } else {
+                        // NOTE Non-scalar keys are not supported by OKH;
+                        //      their values get an unmatchable path.
+                        Some(scalar_key.unwrap_or_else(|| "?".to_owned()))
+                    };
+                }
+                Container::Sequence { next_index } => *next_index += 1,
+            }
+        }
+    }
+
+    /// Block mappings are reported to start after their first key,
+    /// so we move their start back to that key.
+    fn first_key(&mut self, mark: Marker) {
+        if let Some(Frame {
+            path,
+            container: Container::Mapping { empty, .. },
+        }) = self.stack.last_mut()
+            && *empty
+        {
+            *empty = false;
+            let key_start = Location::from(mark);
+            if let Some(start) = self.spans.starts.get_mut(path.as_str()) {
+                *start = key_start.min(*start);
+            }
+        }
+    }
+
+    fn start_container(&mut self, container: Container, mark: Marker) {
+        let node_path = self.node_path();
+        if let Some(path) = &node_path {
+            self.spans.starts.insert(path.clone(), mark.into());
+        }
+        self.stack.push(Frame {
+            path: node_path.unwrap_or_else(|| "?".to_owned()),
+            container,
+        });
+    }
+}
+
+impl MarkedEventReceiver for YamlIndexer {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        match ev {
+            Event::Scalar(value, ..) => {
+                if let Some(path) = self.node_path() {
+                    self.spans.starts.insert(path, mark.into());
+                    self.node_done(None);
+                } else {
+                    self.first_key(mark);
+                    self.node_done(Some(value));
+                }
+            }
+            Event::Alias(_) => {
+                if let Some(path) = self.node_path() {
+                    self.spans.starts.insert(path, mark.into());
+                }
+                self.node_done(None);
+            }
+            Event::MappingStart(_) => {
+                self.start_container(
+                    Container::Mapping {
+                        key: None,
+                        empty: true,
+                    },
+                    mark,
+                );
+            }
+            Event::SequenceStart(_) => {
+                self.start_container(Container::Sequence { next_index: 0 }, mark);
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+                self.node_done(None);
+            }
+            Event::Nothing
+            | Event::StreamStart
+            | Event::StreamEnd
+            | Event::DocumentStart
+            | Event::DocumentEnd => (),
+        }
+    }
+}
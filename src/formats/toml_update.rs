@@ -0,0 +1,156 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Updates existing TOML documents in place.
+//! Only the keys whose values actually changed are touched;
+//! comments, key order and formatting of everything else
+//! (including REUSE/SPDX headers) stay byte-identical.
+
+use std::{error::Error, fs, path::Path};
+
+use toml_edit::{DocumentMut, Item, Table};
+
+use super::SerError;
+
+/// Replaces an item of the existing document with the new one,
+/// recursing into (arrays of) tables where possible,
+/// so only the actually changed leaves get replaced.
+fn update_item(item: &mut Item, old_value: &toml::Value, new_item: &Item, new_value: &toml::Value) {
+    if let (
+        Some(table),
+        toml::Value::Table(old_values),
+        Some(new_table),
+        toml::Value::Table(new_values),
+    ) = (
+        item.as_table_mut(),
+        old_value,
+        new_item.as_table(),
+        new_value,
+    ) {
+        update_table(table, old_values, new_table, new_values);
+        return;
+    }
+    if let (
+        Some(tables),
+        toml::Value::Array(old_values),
+        Some(new_tables),
+        toml::Value::Array(new_values),
+    ) = (
+        item.as_array_of_tables_mut(),
+        old_value,
+        new_item.as_array_of_tables(),
+        new_value,
+    ) && tables.len() == new_tables.len()
+        && old_values.len() == new_values.len()
+    {
+        for (((table, old_table_value), new_table), new_table_value) in tables
+            .iter_mut()
+            .zip(old_values)
+            .zip(new_tables.iter())
+            .zip(new_values)
+        {
+            if let (toml::Value::Table(old_table_values), toml::Value::Table(new_table_values)) =
+                (old_table_value, new_table_value)
+            {
+                update_table(table, old_table_values, new_table, new_table_values);
+            }
+        }
+        return;
+    }
+    if let (Some(value), Some(new_val)) = (item.as_value_mut(), new_item.as_value()) {
+        // keep the whitespace and trailing comment around the value
+        let decor = value.decor().clone();
+        *value = new_val.clone();
+        *value.decor_mut() = decor;
+        return;
+    }
+    *item = new_item.clone();
+}
+
+/// Makes `table` (part of the existing document) hold the data of `new_table`.
+/// `old_values` and `new_values` are the parsed values of both,
+/// which are used to decide what changed.
+fn update_table(
+    table: &mut Table,
+    old_values: &toml::Table,
+    new_table: &Table,
+    new_values: &toml::Table,
+) {
+    for key in old_values.keys() {
+        if !new_values.contains_key(key) {
+            table.remove(key);
+        }
+    }
+    for (key, new_value) in new_values {
+        let Some(new_item) = new_table.get(key) else {
+            continue;
+        };
+        if let (Some(old_value), Some(item)) = (old_values.get(key), table.get_mut(key)) {
+            if old_value != new_value {
+                update_item(item, old_value, new_item, new_value);
+            }
+        } else {
+            table.insert(key, new_item.clone());
+        }
+    }
+}
+
+/// Parses a TOML document both as a document and as data.
+fn parse(toml_str: &str) -> Result<(DocumentMut, toml::Table), Box<dyn Error + Send + Sync>> {
+    Ok((
+        toml_str.parse::<DocumentMut>()?,
+        toml::from_str::<toml::Table>(toml_str)?,
+    ))
+}
+
+/// Returns the `existing` TOML document,
+/// changed to hold the data of the `new` one.
+/// If the data is equal, `existing` is returned unchanged.
+/// If `existing` is not valid TOML,
+/// `new` is returned as it is.
+///
+/// # Errors
+///
+/// If the `new` document is not valid TOML.
+pub fn update(existing: &str, new: &str) -> Result<String, SerError> {
+    log::debug!("Updating existing TOML in place ...");
+    let (new_doc, new_values) = parse(new).map_err(SerError::TomlUpdate)?;
+    let (mut doc, old_values) = match parse(existing) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            log::warn!(
+                "Failed to parse the existing TOML document ({err}); replacing it instead of updating it in place"
+            );
+            return Ok(new.to_owned());
+        }
+    };
+    update_table(
+        doc.as_table_mut(),
+        &old_values,
+        new_doc.as_table(),
+        &new_values,
+    );
+    Ok(doc.to_string())
+}
+
+/// Writes the `new` TOML document to `toml_file`.
+/// If the file already exists, it is updated in place
+/// (see [`update`]) instead of being overwritten.
+///
+/// # Errors
+///
+/// If reading or writing the file fails,
+/// or if `new` is not valid TOML.
+pub fn write_file<OP>(toml_file: OP, new: &str) -> Result<(), SerError>
+where
+    OP: AsRef<Path>,
+{
+    let content = if toml_file.as_ref().exists() {
+        update(&fs::read_to_string(&toml_file)?, new)?
+    } else {
+        new.to_owned()
+    };
+    fs::write(toml_file, content)?;
+    Ok(())
+}
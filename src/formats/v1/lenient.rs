@@ -0,0 +1,344 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Parses OKH v1 manifests that do not fully adhere to the standard,
+//! by converting numbers and booleans where strings are expected,
+//! and leaving out whatever else can not be read,
+//! instead of failing on the first problem.
+
+use std::fmt;
+
+use serde::Serialize;
+use serde_yaml::Value;
+
+use crate::formats::ParseError;
+use crate::formats::spans::{self, Location, Spans};
+
+use super::Okh;
+
+/// What was wrong with a value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagnosticKind {
+    /// A required property is missing
+    MissingField,
+    /// The value is of the wrong type, e.g. a string instead of a list
+    InvalidType,
+    /// The value is of the right type, but not valid,
+    /// e.g. a malformed URL
+    InvalidValue,
+    /// None of the three licenses is set
+    NoLicense,
+}
+
+/// What was done about a problematic value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Recovery {
+    /// The number or boolean was converted to a string
+    Coerced,
+    /// The value (or its enclosing item) was left out
+    Dropped,
+    /// The missing value was replaced by an empty default
+    Defaulted,
+    /// Nothing could be done
+    None,
+}
+
+/// A single problem found while leniently parsing a manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// The path of the problematic property,
+    /// for example `operating-instructions[1].path`
+    pub path: String,
+    /// The path of what was dropped or defaulted,
+    /// which might be an enclosing item of `path`
+    pub affected: String,
+    /// Where in the manifest the problem is located, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    pub message: String,
+    pub recovery: Recovery,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(location) = &self.location {
+            write!(f, "{location}: ")?;
+        }
+        write!(f, "'{}': {}", self.path, self.message)?;
+        match self.recovery {
+            Recovery::Coerced => write!(f, " - converted '{}' to a string", self.affected),
+            Recovery::Dropped => write!(f, " - dropped '{}'", self.affected),
+            Recovery::Defaulted => write!(f, " - set '{}' to an empty value", self.affected),
+            Recovery::None => Ok(()),
+        }
+    }
+}
+
+/// A path within the parsed YAML tree,
+/// as reported by [`serde_path_to_error`].
+#[derive(Debug, Clone)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+fn segments(path: &serde_path_to_error::Path) -> Option<Vec<Segment>> {
+    path.iter()
+        .map(|segment| match segment {
+            serde_path_to_error::Segment::Map { key }
+            | serde_path_to_error::Segment::Enum { variant: key } => {
+                Some(Segment::Key(key.clone()))
+            }
+            serde_path_to_error::Segment::Seq { index } => Some(Segment::Index(*index)),
+            serde_path_to_error::Segment::Unknown => None,
+        })
+        .collect()
+}
+
+fn kind_of(message: &str) -> DiagnosticKind {
+    if message.starts_with("missing field") {
+        DiagnosticKind::MissingField
+    } else if message.starts_with("invalid type") {
+        DiagnosticKind::InvalidType
+    } else {
+        DiagnosticKind::InvalidValue
+    }
+}
+
+/// Extracts the field name from a serde "missing field `name`" message.
+fn missing_field_name(message: &str) -> Option<&str> {
+    message
+        .strip_prefix("missing field `")?
+        .split_once('`')
+        .map(|(name, _)| name)
+}
+
+/// Keeps track of the sequence items dropped so far,
+/// so paths within the partly cleaned tree
+/// can be mapped back to the paths in the original document.
+#[derive(Debug, Default)]
+struct Removals {
+    /// original path of the sequence, original index of the dropped item
+    items: Vec<(String, usize)>,
+}
+
+impl Removals {
+    fn original_index(&self, seq_path: &str, index: usize) -> usize {
+        let mut removed: Vec<usize> = self
+            .items
+            .iter()
+            .filter(|(path, _)| path == seq_path)
+            .map(|(_, removed_index)| *removed_index)
+            .collect();
+        removed.sort_unstable();
+        removed.into_iter().fold(index, |orig, removed_index| {
+            if removed_index <= orig {
+                orig + 1
+            } else {
+                orig
+            }
+        })
+    }
+
+    /// Returns the original path of `segments`,
+    /// and the original index of the last segment, if it is one.
+    fn original_path(&self, segments: &[Segment]) -> (String, Option<usize>) {
+        let mut path = String::new();
+        let mut last_index = None;
+        for segment in segments {
+            match segment {
+                Segment::Key(key) => {
+                    path = spans::join_key(&path, key);
+                    last_index = None;
+                }
+                Segment::Index(index) => {
+                    let orig = self.original_index(&path, *index);
+                    path = spans::join_index(&path, orig);
+                    last_index = Some(orig);
+                }
+            }
+        }
+        (path, last_index)
+    }
+
+    fn add(&mut self, segments: &[Segment]) {
+        if let Some((_, parent)) = segments.split_last() {
+            let (seq_path, _) = self.original_path(parent);
+            if let (_, Some(orig)) = self.original_path(segments) {
+                self.items.push((seq_path, orig));
+            }
+        }
+    }
+}
+
+/// Returns the value at `segments` within the tree.
+fn get_mut<'a>(value: &'a mut Value, segments: &[Segment]) -> Option<&'a mut Value> {
+    segments
+        .iter()
+        .try_fold(value, |parent, segment| match segment {
+            Segment::Key(key) => parent.get_mut(key.as_str()),
+            Segment::Index(index) => parent.get_mut(*index),
+        })
+}
+
+/// Replaces the number or boolean at `segments` with its string form.
+/// Returns whether it was such a value.
+fn coerce_to_string(value: &mut Value, segments: &[Segment]) -> bool {
+    let Some(scalar) = get_mut(value, segments) else {
+        return false;
+    };
+    let string = match scalar {
+        Value::Number(number) => number.to_string(),
+        Value::Bool(boolean) => boolean.to_string(),
+        Value::Null
+        | Value::String(_)
+        | Value::Sequence(_)
+        | Value::Mapping(_)
+        | Value::Tagged(_) => return false,
+    };
+    *scalar = Value::String(string);
+    true
+}
+
+/// Removes the value at `segments` from the tree.
+/// Returns whether it was found.
+fn remove(value: &mut Value, segments: &[Segment]) -> bool {
+    let Some((last, parent_segments)) = segments.split_last() else {
+        return false;
+    };
+    let Some(parent) = get_mut(value, parent_segments) else {
+        return false;
+    };
+    match (last, parent) {
+        (Segment::Key(key), Value::Mapping(mapping)) => mapping.remove(key.as_str()).is_some(),
+        (Segment::Index(index), Value::Sequence(sequence)) => {
+            if *index < sequence.len() {
+                sequence.remove(*index);
+                true
+            } else {
+                false
+            }
+        }
+        (Segment::Key(_) | Segment::Index(_), _) => false,
+    }
+}
+
+/// Coerces, drops or defaults the problematic value at `segments`,
+/// so the next parsing attempt gets further.
+/// Returns the original paths of the problematic property
+/// and of what was coerced, dropped or defaulted,
+/// or `None` if nothing can be done.
+fn recover(
+    tree: &mut Value,
+    defaults: &Value,
+    segments: &[Segment],
+    kind: DiagnosticKind,
+    message: &str,
+    removals: &mut Removals,
+) -> Option<(String, String, Recovery)> {
+    let (mut path, _) = removals.original_path(segments);
+    if kind == DiagnosticKind::MissingField {
+        // NOTE serde reports the enclosing value as the location
+        //      of a missing field.
+        let field = missing_field_name(message)?;
+        let enclosing = path.clone();
+        path = spans::join_key(&path, field);
+        if segments.is_empty() {
+            // A required top-level property; we can not drop the root
+            let default = defaults.get(field)?.clone();
+            let Value::Mapping(root) = tree else {
+                return None;
+            };
+            root.insert(Value::from(field), default);
+            return Some((path.clone(), path, Recovery::Defaulted));
+        }
+        if !remove(tree, segments) {
+            return None;
+        }
+        removals.add(segments);
+        return Some((path, enclosing, Recovery::Dropped));
+    }
+    if kind == DiagnosticKind::InvalidType
+        && message.ends_with("expected a string")
+        && coerce_to_string(tree, segments)
+    {
+        return Some((path.clone(), path, Recovery::Coerced));
+    }
+    if !remove(tree, segments) {
+        return None;
+    }
+    removals.add(segments);
+    Some((path.clone(), path, Recovery::Dropped))
+}
+
+impl Okh {
+    /// Parses an OKH v1 manifest as far as possible.
+    /// Numbers and booleans where strings are expected
+    /// are converted to strings.
+    /// Other properties that can not be read are left out
+    /// (or if they are required at the top-level, set to an empty value),
+    /// and reported as diagnostics.
+    /// If an item of a list can not be read, only that item is left out.
+    ///
+    /// # Errors
+    ///
+    /// If the input is not YAML at all,
+    /// or its top-level is not a mapping.
+    pub fn from_yaml_lenient(yaml_str: &str) -> Result<(Self, Vec<Diagnostic>), ParseError> {
+        log::debug!("Leniently parsing YAML to v1 ...");
+        let mut tree: Value = serde_yaml::from_str(yaml_str)?;
+        let defaults = serde_yaml::to_value(Self::default())?;
+        let spans = Spans::from_yaml(yaml_str);
+        let mut removals = Removals::default();
+        let mut diagnostics = vec![];
+        loop {
+            let err = match serde_path_to_error::deserialize::<_, Self>(tree.clone()) {
+                Ok(parsed) => {
+                    if parsed.has_no_license() {
+                        diagnostics.push(Diagnostic {
+                            kind: DiagnosticKind::NoLicense,
+                            path: "license".to_owned(),
+                            affected: "license".to_owned(),
+                            location: spans.get_closest("license"),
+                            message: ParseError::NoLicense.to_string(),
+                            recovery: Recovery::None,
+                        });
+                    }
+                    return Ok((parsed, diagnostics));
+                }
+                Err(err) => err,
+            };
+            let Some(err_segments) = segments(err.path()) else {
+                return Err(err.into_inner().into());
+            };
+            let message = err.inner().to_string();
+            let kind = kind_of(&message);
+            let (err_path, _) = removals.original_path(&err_segments);
+            let location = spans.get_closest(&err_path);
+            let Some((path, affected, recovery)) = recover(
+                &mut tree,
+                &defaults,
+                &err_segments,
+                kind,
+                &message,
+                &mut removals,
+            ) else {
+                return Err(err.into_inner().into());
+            };
+            log::debug!("Lenient parsing: '{path}': {message}");
+            diagnostics.push(Diagnostic {
+                kind,
+                path,
+                affected,
+                location,
+                message,
+                recovery,
+            });
+        }
+    }
+}
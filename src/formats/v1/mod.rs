@@ -2,7 +2,11 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use std::{fs, path::Path};
+use std::{
+    fs,
+    io::{Read, Write},
+    path::Path,
+};
 
 use regex::Regex;
 use relative_path::RelativePathBuf;
@@ -13,15 +17,21 @@ use crate::macros::rgx;
 
 use super::{Locator, ParseError, SerError};
 
+pub mod lenient;
+
 type DSString = String;
 
+pub const FORMAT_NAME: &str = "OKH-v1";
+
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct Document {
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<DSString>,
 
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub path: Option<Locator>,
 }
 
@@ -59,13 +69,31 @@ pub struct Person {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub email: Option<DSString>,
 
-    /// This is only used in the [`create::v1::Okh::contact`] property.
+    /// This is only used in the [`crate::formats::v1::Okh::contact`] property.
     #[cfg(feature = "v1_non_losh")]
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub social: Vec<Social>,
 }
 
+/// A persons account on a social media platform.
+#[cfg(feature = "v1_non_losh")]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct Social {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<DSString>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_handle: Option<DSString>,
+}
+
+/// A [`Person`] that may also list social media accounts.
+#[cfg(feature = "v1_non_losh")]
+pub type PersonSocial = Person;
+
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct Standard {
@@ -323,20 +351,44 @@ impl Okh {
         log::debug!("Parsing YAML to v1 ...");
         let parsed = serde_yaml::from_str::<Self>(yaml_str)?;
 
-        if (
-            &parsed.license.documentation,
-            &parsed.license.hardware,
-            &parsed.license.software,
-        ) == (&None, &None, &None)
-        {
-            panic!(
-                "at least one of the three licenses (documentation, hardware or software) has to be set"
-            );
+        if parsed.has_no_license() {
+            return Err(ParseError::NoLicense);
         }
 
         Ok(parsed)
     }
 
+    /// Whether none of the three licenses is set.
+    pub const fn has_no_license(&self) -> bool {
+        self.license.documentation.is_none()
+            && self.license.hardware.is_none()
+            && self.license.software.is_none()
+    }
+
+    pub fn from_yaml_reader<R: Read>(mut reader: R) -> Result<Self, ParseError> {
+        log::debug!("Reading YAML to string ...");
+        let mut yaml_str = String::new();
+        reader.read_to_string(&mut yaml_str)?;
+
+        Self::from_yaml(&yaml_str)
+    }
+
+    /// See [`Self::from_yaml_lenient`].
+    ///
+    /// # Errors
+    ///
+    /// If reading fails,
+    /// or the input is not YAML at all.
+    pub fn from_yaml_reader_lenient<R: Read>(
+        mut reader: R,
+    ) -> Result<(Self, Vec<lenient::Diagnostic>), ParseError> {
+        log::debug!("Reading YAML to string ...");
+        let mut yaml_str = String::new();
+        reader.read_to_string(&mut yaml_str)?;
+
+        Self::from_yaml_lenient(&yaml_str)
+    }
+
     pub fn from_yaml_file<IP>(yaml_file: IP) -> Result<Self, ParseError>
     where
         IP: AsRef<Path>,
@@ -352,6 +404,13 @@ impl Okh {
         Ok(serde_yaml::to_string(self)?)
     }
 
+    pub fn to_yaml_writer<W: Write>(&self, mut writer: W) -> Result<(), SerError> {
+        let serialized = self.to_yaml()?;
+        log::debug!("Writing v1 YAML ...");
+        writer.write_all(serialized.as_bytes())?;
+        Ok(())
+    }
+
     pub fn to_yaml_file<OP>(&self, yaml_file: OP) -> Result<(), SerError>
     where
         OP: AsRef<Path>,
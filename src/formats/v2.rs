@@ -2,7 +2,11 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use std::{fs, path::Path};
+use std::{
+    fs,
+    io::{Read, Write},
+    path::Path,
+};
 
 use regex::Regex;
 use relative_path::RelativePathBuf;
@@ -14,12 +18,63 @@ use crate::{
     oxrl::{Odrl, Otrl},
 };
 
-use super::{ParseError, SerError};
+use super::{ParseError, SerError, rdf};
 
 pub type DSString = String;
 
 pub const OKHV: &str = "OKH-LOSHv1.0";
+pub const FORMAT_NAME: &str = "OKH-LOSH";
+pub const FORMAT_NAME_JSON: &str = "OKH-LOSH-JSON";
+pub const FORMAT_NAME_JSONLD: &str = "OKH-LOSH-JSON-LD";
 pub const MANIFEST_FILE_NAME: &str = "okh.toml";
+/// The (kebab-case) names of the top-level properties of a manifest
+pub const KEYS: &[&str] = &[
+    "okhv",
+    "name",
+    "repo",
+    "version",
+    "license",
+    "licensor",
+    "upload-method",
+    "organisation",
+    "readme",
+    "contribution-guide",
+    "image",
+    "documentation-language",
+    "attestation",
+    "standard-compliance",
+    "cpc-patent-class",
+    "tsdc",
+    "bom",
+    "release",
+    "timestamp",
+    "fork-of",
+    "function",
+    "documentation-readiness-level",
+    "technology-readiness-level",
+    "user-manual",
+    "manufacturing-instructions",
+    "software",
+    "standard",
+    "source",
+    "export",
+    "auxiliary",
+    "license-aspects",
+    "part",
+];
+/// The (kebab-case) names of the properties of a [`Software`]
+pub const SOFTWARE_KEYS: &[&str] = &["label", "release"];
+/// The (kebab-case) names of the properties of a [`SubMosh`],
+/// which are those of a manifest that are kept when it becomes a sub-part
+pub const SUB_MOSH_KEYS: &[&str] = &[
+    "name",
+    "image",
+    "tsdc",
+    "source",
+    "export",
+    "auxiliary",
+    "part",
+];
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
@@ -72,6 +127,27 @@ pub struct SubMosh {
     pub part: Vec<SubMosh>,
 }
 
+/// Which license applies to which aspect of the project.
+/// This is an extension to OKH LOSH,
+/// which only knows a single `license` for the whole project;
+/// it is used when that one is combined from multiple licenses,
+/// as is common in OKH v1 manifests.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct LicenseAspects {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hardware: Option<DSString>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<DSString>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub software: Option<DSString>,
+}
+
 // #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
 // #[serde(rename_all = "kebab-case")]
 // pub struct Meta {
@@ -206,6 +282,11 @@ pub struct Okh {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub auxiliary: Vec<RelativePathBuf>,
 
+    /// LOSH extension; see [`LicenseAspects`]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license_aspects: Option<LicenseAspects>,
+
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub part: Vec<SubMosh>,
@@ -215,6 +296,28 @@ pub struct Okh {
 }
 
 impl Okh {
+    /// Splits the licensor into name and e-mail,
+    /// as in `"Jane Doe <jane@example.org>"`.
+    /// Either may be missing.
+    pub fn licensor_name_and_email(&self) -> (Option<&str>, Option<&str>) {
+        let licensor = self.licensor.trim();
+        let matcher = rgx!(r"^(.*?)\s*<([^>]*)>$");
+        let (name, email) = matcher
+            .captures(licensor)
+            .map_or((Some(licensor), None), |caps| {
+                (
+                    caps.get(1).map(|mtch| mtch.as_str()),
+                    caps.get(2).map(|mtch| mtch.as_str()),
+                )
+            });
+        (
+            name.map(str::trim).filter(|name_val| !name_val.is_empty()),
+            email
+                .map(str::trim)
+                .filter(|email_val| !email_val.is_empty()),
+        )
+    }
+
     pub fn from_toml(toml_str: &str) -> Result<Self, ParseError> {
         log::debug!("Parsing TOML to v2 ...");
         let parsed = toml::from_str::<Self>(toml_str)?;
@@ -222,6 +325,14 @@ impl Okh {
         Ok(parsed)
     }
 
+    pub fn from_toml_reader<R: Read>(mut reader: R) -> Result<Self, ParseError> {
+        log::debug!("Reading TOML to string ...");
+        let mut toml_str = String::new();
+        reader.read_to_string(&mut toml_str)?;
+
+        Self::from_toml(&toml_str)
+    }
+
     pub fn from_toml_file<OP>(toml_file: OP) -> Result<Self, ParseError>
     where
         OP: AsRef<Path>,
@@ -237,13 +348,111 @@ impl Okh {
         Ok(toml::to_string(self)?)
     }
 
+    pub fn to_toml_writer<W: Write>(&self, mut writer: W) -> Result<(), SerError> {
+        let serialized = self.to_toml()?;
+        log::debug!("Writing TOML ...");
+        writer.write_all(serialized.as_bytes())?;
+        Ok(())
+    }
+
+    /// Writes the manifest to a TOML file.
+    /// An existing file is updated in place,
+    /// preserving its comments and formatting.
     pub fn to_toml_file<OP>(&self, toml_file: OP) -> Result<(), SerError>
     where
         OP: AsRef<Path>,
     {
         let serialized = self.to_toml()?;
         log::debug!("Writing to TOML file ...");
-        fs::write(toml_file, serialized)?;
+        super::toml_update::write_file(toml_file, &serialized)
+    }
+
+    pub fn from_json(json_str: &str) -> Result<Self, ParseError> {
+        log::debug!("Parsing JSON to v2 ...");
+        Ok(serde_json::from_str::<Self>(json_str)?)
+    }
+
+    pub fn from_json_reader<R: Read>(mut reader: R) -> Result<Self, ParseError> {
+        log::debug!("Reading JSON to string ...");
+        let mut json_str = String::new();
+        reader.read_to_string(&mut json_str)?;
+
+        Self::from_json(&json_str)
+    }
+
+    pub fn from_json_file<OP>(json_file: OP) -> Result<Self, ParseError>
+    where
+        OP: AsRef<Path>,
+    {
+        log::debug!("Reading JSON file to string ...");
+        let json_str = fs::read_to_string(json_file)?;
+
+        Self::from_json(&json_str)
+    }
+
+    pub fn to_json(&self) -> Result<String, SerError> {
+        log::debug!("Serializing to JSON ...");
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn to_json_writer<W: Write>(&self, mut writer: W) -> Result<(), SerError> {
+        let serialized = self.to_json()?;
+        log::debug!("Writing JSON ...");
+        writer.write_all(serialized.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn to_json_file<OP>(&self, json_file: OP) -> Result<(), SerError>
+    where
+        OP: AsRef<Path>,
+    {
+        let serialized = self.to_json()?;
+        log::debug!("Writing to JSON file ...");
+        fs::write(json_file, serialized)?;
+        Ok(())
+    }
+
+    /// Serializes to JSON-LD,
+    /// which is the plain JSON serialization
+    /// plus an `@context` mapping the properties to the OKH LOSH ontology
+    /// and an `@id` (`<repo>#module`, as in Turtle),
+    /// minus the properties that the ontology lacks.
+    pub fn to_jsonld(&self) -> Result<String, SerError> {
+        log::debug!("Serializing to JSON-LD ...");
+        let mut jsonld = serde_json::Map::new();
+        jsonld.insert("@context".to_owned(), rdf::jsonld_context(&self.repo));
+        // NOTE The same IRI as the module gets in Turtle
+        jsonld.insert(
+            "@id".to_owned(),
+            rdf::module_iri(&self.repo, rdf::MODULE_FRAGMENT)
+                .as_str()
+                .into(),
+        );
+        jsonld.insert(
+            "@type".to_owned(),
+            format!("okh:{}", rdf::class::MODULE).into(),
+        );
+        if let serde_json::Value::Object(mut props) = serde_json::to_value(self)? {
+            props.remove(rdf::UNMAPPED_PROP);
+            jsonld.extend(props);
+        }
+        Ok(serde_json::to_string_pretty(&jsonld)?)
+    }
+
+    pub fn to_jsonld_writer<W: Write>(&self, mut writer: W) -> Result<(), SerError> {
+        let serialized = self.to_jsonld()?;
+        log::debug!("Writing JSON-LD ...");
+        writer.write_all(serialized.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn to_jsonld_file<OP>(&self, jsonld_file: OP) -> Result<(), SerError>
+    where
+        OP: AsRef<Path>,
+    {
+        let serialized = self.to_jsonld()?;
+        log::debug!("Writing to JSON-LD file ...");
+        fs::write(jsonld_file, serialized)?;
         Ok(())
     }
 
@@ -254,4 +463,30 @@ impl Okh {
     pub fn file_matcher() -> &'static Regex {
         rgx!(r"okh\.[tT][oO][mM][lL]$")
     }
+
+    pub fn json_ext_matcher() -> &'static Regex {
+        rgx!(r"(^|\.)[jJ][sS][oO][nN]$")
+    }
+
+    pub fn json_file_matcher() -> &'static Regex {
+        rgx!(r"okh\.[jJ][sS][oO][nN]$")
+    }
+
+    pub fn jsonld_ext_matcher() -> &'static Regex {
+        rgx!(r"(^|\.)[jJ][sS][oO][nN][lL][dD]$")
+    }
+
+    pub fn jsonld_file_matcher() -> &'static Regex {
+        rgx!(r"okh\.[jJ][sS][oO][nN][lL][dD]$")
+    }
+
+    /// Matches the extensions of all the OKH LOSH serializations
+    /// that can be validated with the JSON schema directly
+    pub fn validatable_ext_matcher() -> &'static Regex {
+        rgx!(r"(^|\.)([tT][oO][mM][lL]|[jJ][sS][oO][nN])$")
+    }
+
+    pub fn validatable_file_matcher() -> &'static Regex {
+        rgx!(r"okh\.([tT][oO][mM][lL]|[jJ][sS][oO][nN])$")
+    }
 }
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 mod language;
+pub mod ownership;
 
 use chrono::Datelike;
 use projvar::environment::Environment;
@@ -18,10 +19,14 @@ use std::process::Command;
 use std::{fs, path::Path};
 use url::Url;
 
-use crate::formats::SerError;
+use crate::conversion::v2_to_citation;
+use crate::formats::citation;
+use crate::formats::toml_update;
 use crate::formats::v2;
 use crate::formats::v2::SubMosh;
+use crate::formats::{ParseError, SerError};
 use crate::macros::rgx;
+use crate::preview::Preview;
 use crate::{dir, file_types, license};
 
 #[derive(thiserror::Error, Debug)]
@@ -52,11 +57,60 @@ pub enum Error {
 
     #[error("Failed to cast a string to a URL.")]
     NotaUrl(#[from] url::ParseError),
+
+    #[error("Failed to parse an existing manifest or config file.")]
+    Parse(#[from] ParseError),
 }
 
 type Res<O> = Result<O, Error>;
 type ORelPath = Option<RelativePathBuf>;
 
+/// Settings that influence how generated files are written.
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// Whether to overwrite existing files
+    pub overwrite: bool,
+    /// Whether to update existing manifests,
+    /// re-deriving only the machine-owned fields
+    pub update: bool,
+    /// Which fields are re-derived when updating
+    pub ownership: ownership::Ownership,
+    /// Whether to only show what would be written
+    pub preview: Preview,
+}
+
+impl Options {
+    /// Whether `file` should be (over-)written,
+    /// or in case of a preview, shown.
+    /// Only files that get merged when updating (`updatable`)
+    /// are written on update without `--overwrite`.
+    fn should_write(&self, file: &Path, updatable: bool) -> bool {
+        let replace = self.overwrite || (self.update && updatable) || self.preview.is_active();
+        if file.exists() && !replace {
+            let hint = if updatable {
+                "`--overwrite` and `--update`"
+            } else {
+                "`--overwrite`"
+            };
+            log::warn!(
+                "Skipped writing '{}': File already exists. See {hint}.",
+                file.display()
+            );
+            false
+        } else {
+            true
+        }
+    }
+}
+
+/// Parses an OKH LOSH manifest into a data tree,
+/// for diffing.
+fn okh_losh_value(toml_str: &str) -> Option<serde_json::Value> {
+    v2::Okh::from_toml(toml_str)
+        .ok()
+        .and_then(|okh| serde_json::to_value(okh).ok())
+}
+
 #[inline]
 fn pv(environment: &projvar::environment::Environment, key: Key) -> Res<String> {
     Ok(environment
@@ -157,7 +211,7 @@ fn find_parts(
     rec_files_groups: &[Vec<RelativePathBuf>],
     module_dir: &Path,
     environment: &Environment,
-    overwrite: bool,
+    options: &Options,
 ) -> Res<Vec<SubMosh>> {
     // will map a dir (making up a part/sub-module)
     // to the relevant file-names within:
@@ -219,9 +273,9 @@ fn find_parts(
         if !files.contains(&okh_toml_name_rel_path) {
             // generate child okh.toml file
             if is_git_submodule(&sub_part_path) {
-                okh_losh_toml_part(&sub_part_path, &cwd, None, overwrite)?; // TODO is overwrite inheritance ok/save? :/
+                okh_losh_toml_part(&sub_part_path, &cwd, None, options)?; // TODO is overwrite inheritance ok/save? :/
             } else {
-                okh_losh_toml_part(module_dir, &sub_part_dir, Some(environment), overwrite)?;
+                okh_losh_toml_part(module_dir, &sub_part_dir, Some(environment), options)?;
                 // TODO is overwrite inheritance ok/save? :/
             }
         }
@@ -254,7 +308,7 @@ fn find_parts(
     Ok(part)
 }
 
-fn generate_data(module_dir: &Path, environment: &Environment, overwrite: bool) -> Res<v2::Okh> {
+fn generate_data(module_dir: &Path, environment: &Environment, options: &Options) -> Res<v2::Okh> {
     let license = license::ensure_spdx_license_id(&pv(environment, Key::License)?);
 
     let documentation_language = language::identify(&fs::read_to_string("README.md")?); // TODO HACK look for README* instead!
@@ -273,7 +327,7 @@ fn generate_data(module_dir: &Path, environment: &Environment, overwrite: bool)
     //     "UTC now in a custom format is: {}",
     //     now.format("%a %b %e %T %Y")
     // );
-    let timestamp = Some(now.to_rfc2822());
+    let timestamp = Some(now.to_rfc3339());
 
     // let licensor = "ANONYMOUS".to_owned();
     // NOTE FIXME - As licensor, we use the git user of the first commit; but what about the case of a fork?
@@ -293,7 +347,7 @@ fn generate_data(module_dir: &Path, environment: &Environment, overwrite: bool)
     let rec_files_groups = find_rec_files(module_dir); // TODO Use git list if git repo, otherwise filesystem list - does projvar already have this, or only osh-tool (Nim :/) ?
     let image = rec_files_groups.first().unwrap();
 
-    let part = find_parts(&rec_files_groups, module_dir, environment, overwrite)?;
+    let part = find_parts(&rec_files_groups, module_dir, environment, options)?;
 
     let upload_method = Some("manifest".to_string()); // TODO cleanup this whole property in the specs
 
@@ -316,7 +370,7 @@ fn generate_data(module_dir: &Path, environment: &Environment, overwrite: bool)
         standard_compliance: vec![],
         cpc_patent_class: None,
         release: None, // TODO Fetch from GH/GL API NOTE This is not the version, but the URL to the release page/archive
-        timestamp,     // TODO Which format should this have?
+        timestamp,
         fork_of: None, // TODO Check GH/GL API (most/all rust libraries for these APIs seem not to support this, so might have to be done by manually querying the APIs)
         function: None, // TODO Get these from GH/GL API Labels/Tags, maybe? -> checked already; neither GH nor GL APIs offer to fetch thee; would have to be parsed from the HTML of the web-view
         documentation_readiness_level: None, // TODO
@@ -328,16 +382,46 @@ fn generate_data(module_dir: &Path, environment: &Environment, overwrite: bool)
         source: vec![], // TODO -> scan for known source files extensions (but not inside parts folders)
         export: vec![], // TODO -> scan for known export files extensions (but not inside parts folders)
         auxiliary: vec![], // TODO -> scan for known aux. files extensions (but not inside parts folders)
+        license_aspects: None,
         part,
     })
 }
 
+/// Constructs the REUSE/SPDX license header for a new manifest.
+fn reuse_header() -> Res<String> {
+    let git_user_name_dirty = String::from_utf8(
+        Command::new("git")
+            .arg("config")
+            .arg("user.name")
+            .output()?
+            .stdout,
+    )?;
+    let git_user_name = git_user_name_dirty.trim_end();
+    let git_user_email_dirty = String::from_utf8(
+        Command::new("git")
+            .arg("config")
+            .arg("user.email")
+            .output()?
+            .stdout,
+    )?;
+    let git_user_email = git_user_email_dirty.trim_end();
+    let now: DateTime<Utc> = Utc::now();
+    Ok(format!(
+        "# SPDX-FileCopyrightText: {} {} <{}>
+#
+# SPDX-License-Identifier: CC0-1.0",
+        now.year(),
+        git_user_name,
+        git_user_email
+    ))
+}
+
 pub fn okh_losh_toml_part(
     repo_root: &Path,
     sub_part: &RelativePath,
     environment: Option<&Environment>,
-    overwrite: bool,
-) -> Res<()> {
+    options: &Options,
+) -> Res<v2::Okh> {
     let owned_env = if environment.is_some() {
         None
     } else {
@@ -353,56 +437,64 @@ pub fn okh_losh_toml_part(
     let module_dir = sub_part.to_path(repo_root);
     // log::info!("XXX ran projvar in '{}' - '{}'.", repo_root.display(), sub_part);
 
-    let okh_losh = generate_data(&module_dir, environment_val, overwrite)?;
+    let generated = generate_data(&module_dir, environment_val, options)?;
 
     let manifest_file = module_dir.join(v2::MANIFEST_FILE_NAME);
-    if !manifest_file.exists() || overwrite {
+    let okh_losh = if options.update && manifest_file.exists() {
+        let existing = v2::Okh::from_toml_file(&manifest_file)?;
+        options.ownership.merge(&existing, &generated)?
+    } else {
+        generated
+    };
+    if options.should_write(&manifest_file, true) {
         log::debug!("Writing to TOML file ...");
-
-        // construct the REUSE/SPDX license header
-        let git_user_name_dirty = String::from_utf8(
-            Command::new("git")
-                .arg("config")
-                .arg("user.name")
-                .output()?
-                .stdout,
-        )?;
-        let git_user_name = git_user_name_dirty.trim_end();
-        let git_user_email_dirty = String::from_utf8(
-            Command::new("git")
-                .arg("config")
-                .arg("user.email")
-                .output()?
-                .stdout,
-        )?;
-        let git_user_email = git_user_email_dirty.trim_end();
-        let now: DateTime<Utc> = Utc::now();
-        let header = format!(
-            "# SPDX-FileCopyrightText: {} {} <{}>
-#
-# SPDX-License-Identifier: CC0-1.0",
-            now.year(),
-            git_user_name,
-            git_user_email
-        );
-
         let content = okh_losh.to_toml()?;
-
-        fs::write(manifest_file, format!("{header}\n\n{content}"))?;
-    } else {
-        log::warn!(
-            "Skipped writing '{}': File already exists. See `--overwrite`.",
-            manifest_file.display()
-        );
+        let full_content = if manifest_file.exists() {
+            // keeps the REUSE/SPDX header and manual comments and formatting
+            toml_update::update(&fs::read_to_string(&manifest_file)?, &content)?
+        } else {
+            format!("{}\n\n{content}", reuse_header()?)
+        };
+        options
+            .preview
+            .write(&manifest_file, &full_content, okh_losh_value)?;
     }
 
-    Ok(())
+    Ok(okh_losh)
 }
 
 fn is_git_submodule(sub_part_path: &Path) -> bool {
     git2::Repository::open(sub_part_path).is_ok()
 }
 
-pub fn okh_losh_toml(proj_root: &Path, overwrite: bool) -> Res<()> {
-    okh_losh_toml_part(proj_root, &RelativePathBuf::new(), None, overwrite)
+/// Writes `CITATION.cff` and `codemeta.json` files,
+/// derived from the manifest.
+fn citation_files(proj_root: &Path, okh_losh: &v2::Okh, options: &Options) -> Res<()> {
+    let cff_file = proj_root.join(citation::CFF_FILE_NAME);
+    if options.should_write(&cff_file, false) {
+        let (cff, _report) = v2_to_citation::to_cff(okh_losh);
+        options
+            .preview
+            .write(&cff_file, &cff.to_yaml()?, |yaml_str| {
+                serde_yaml::from_str(yaml_str).ok()
+            })?;
+    }
+    let codemeta_file = proj_root.join(citation::CODEMETA_FILE_NAME);
+    if options.should_write(&codemeta_file, false) {
+        let (codemeta, _report) = v2_to_citation::to_codemeta(okh_losh);
+        options
+            .preview
+            .write(&codemeta_file, &codemeta.to_json()?, |json_str| {
+                serde_json::from_str(json_str).ok()
+            })?;
+    }
+    Ok(())
+}
+
+pub fn okh_losh_toml(proj_root: &Path, options: &Options, citation: bool) -> Res<()> {
+    let okh_losh = okh_losh_toml_part(proj_root, &RelativePathBuf::new(), None, options)?;
+    if citation {
+        citation_files(proj_root, &okh_losh, options)?;
+    }
+    Ok(())
 }
@@ -0,0 +1,106 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Which (top-level) fields of a manifest are owned by the machine,
+//! and thus get re-derived when updating an existing manifest,
+//! and which are owned by humans, and thus are kept as they are.
+//!
+//! The [`Default`] represents the built-in behavior.
+//! An ownership file overrides it, for example:
+//!
+//! ```toml
+//! machine = ["version", "timestamp", "image", "release"]
+//! ```
+
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Deserializer, de};
+
+use crate::formats::{ParseError, v2};
+
+use super::Res;
+
+/// Which fields of a manifest are re-derived when updating it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Ownership {
+    /// The (kebab-case) names of the machine-owned fields;
+    /// all others are human-owned.
+    #[serde(deserialize_with = "manifest_keys")]
+    pub machine: Vec<String>,
+}
+
+/// Deserializes a list of top-level manifest property names,
+/// failing on names that are not OKH LOSH properties.
+fn manifest_keys<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let keys = Vec::<String>::deserialize(deserializer)?;
+    if let Some(unknown) = keys.iter().find(|key| !v2::KEYS.contains(&key.as_str())) {
+        return Err(de::Error::unknown_field(unknown, v2::KEYS));
+    }
+    Ok(keys)
+}
+
+impl Default for Ownership {
+    fn default() -> Self {
+        Self {
+            machine: ["version", "timestamp", "image", "source", "export", "part"]
+                .into_iter()
+                .map(ToOwned::to_owned)
+                .collect(),
+        }
+    }
+}
+
+impl Ownership {
+    pub fn from_toml(toml_str: &str) -> Result<Self, ParseError> {
+        log::debug!("Parsing field ownership TOML ...");
+        Ok(toml::from_str::<Self>(toml_str)?)
+    }
+
+    pub fn from_toml_file<OP>(toml_file: OP) -> Result<Self, ParseError>
+    where
+        OP: AsRef<Path>,
+    {
+        log::debug!("Reading field ownership TOML file to string ...");
+        let toml_str = fs::read_to_string(toml_file)?;
+
+        Self::from_toml(&toml_str)
+    }
+
+    pub fn is_machine_owned(&self, field: &str) -> bool {
+        self.machine
+            .iter()
+            .any(|machine_field| machine_field == field)
+    }
+
+    /// Merges a freshly generated manifest into an existing one.
+    /// Machine-owned fields are taken from the generated manifest,
+    /// human-owned ones from the existing manifest.
+    /// Human-owned fields missing in the existing manifest
+    /// are filled in from the generated one.
+    ///
+    /// # Errors
+    ///
+    /// If (de-)serializing either of the manifests fails.
+    pub fn merge(&self, existing: &v2::Okh, generated: &v2::Okh) -> Res<v2::Okh> {
+        log::debug!("Merging the generated manifest into the existing one ...");
+        let existing_fields =
+            toml::from_str::<toml::Table>(&existing.to_toml()?).map_err(ParseError::from)?;
+        let mut merged =
+            toml::from_str::<toml::Table>(&generated.to_toml()?).map_err(ParseError::from)?;
+        for (field, value) in existing_fields {
+            if self.is_machine_owned(&field) {
+                log::trace!("Re-deriving machine-owned field '{field}'");
+            } else {
+                merged.insert(field, value);
+            }
+        }
+        Ok(toml::Value::Table(merged)
+            .try_into::<v2::Okh>()
+            .map_err(ParseError::from)?)
+    }
+}
@@ -3,11 +3,14 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 mod conversion;
+mod file_types;
+mod file_types_format;
 mod formats;
 mod license;
 mod logger;
 mod macros;
 mod oxrl;
+mod sanitize;
 mod validation;
 
 use git_version::git_version;
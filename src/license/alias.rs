@@ -0,0 +1,287 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Maps the free-form license names found in the wild
+//! (e.g. "CERN OHL v1.2", "CC BY-SA 4.0" or "GPLv3")
+//! to SPDX license identifiers.
+//!
+//! Names are compared in a simplified form,
+//! ignoring case, whitespace, punctuation,
+//! words like "license", "version" and "GNU",
+//! and trailing ".0"s in version numbers.
+//! Thus "GNU GPL v3.0", "gpl-3" and "GPLv3" are all the same,
+//! and a single alias covers them all.
+
+use std::{fmt, sync::LazyLock};
+
+use super::validate_spdx_expr;
+
+/// How sure we are that a mapped license is the one that was meant.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    /// Nothing matched; a `LicenseRef-` was created.
+    None,
+    /// The name is merely similar to a known license name.
+    Low,
+    /// The name is a known alias,
+    /// but the version or variant had to be guessed.
+    Medium,
+    /// The name is a known, unambiguous alias,
+    /// or equal to an SPDX ID or license name.
+    High,
+    /// The name already was a valid SPDX expression.
+    Exact,
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::None => "none",
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Exact => "exact",
+        })
+    }
+}
+
+/// The result of mapping a license name to SPDX.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    /// A valid SPDX expression
+    pub expression: String,
+    pub confidence: Confidence,
+}
+
+/// The minimal similarity (0.0 - 1.0) of the simplified names
+/// for a fuzzy match.
+const MIN_SIMILARITY: f64 = 0.85;
+
+/// Common license names that are not SPDX IDs or SPDX license names,
+/// and the SPDX ID they map to.
+/// GNU license names without "only" or "or later"
+/// map to the "only" variant, which is a guess.
+const ALIASES: &[(&str, &str, Confidence)] = &[
+    ("GPLv2", "GPL-2.0-only", Confidence::Medium),
+    ("GPLv3", "GPL-3.0-only", Confidence::Medium),
+    ("LGPLv2.1", "LGPL-2.1-only", Confidence::Medium),
+    ("LGPLv3", "LGPL-3.0-only", Confidence::Medium),
+    ("AGPLv3", "AGPL-3.0-only", Confidence::Medium),
+    (
+        "GNU General Public License v2",
+        "GPL-2.0-only",
+        Confidence::Medium,
+    ),
+    (
+        "GNU General Public License v3",
+        "GPL-3.0-only",
+        Confidence::Medium,
+    ),
+    (
+        "GNU Lesser General Public License v3",
+        "LGPL-3.0-only",
+        Confidence::Medium,
+    ),
+    (
+        "GNU Lesser General Public License v2.1",
+        "LGPL-2.1-only",
+        Confidence::Medium,
+    ),
+    (
+        "GNU Affero General Public License v3",
+        "AGPL-3.0-only",
+        Confidence::Medium,
+    ),
+    ("TAPR OHL", "TAPR-OHL-1.0", Confidence::High),
+    (
+        "TAPR Open Hardware License",
+        "TAPR-OHL-1.0",
+        Confidence::High,
+    ),
+    ("CC0", "CC0-1.0", Confidence::High),
+    ("Creative Commons Zero", "CC0-1.0", Confidence::High),
+    ("CC BY 4.0 International", "CC-BY-4.0", Confidence::High),
+    (
+        "CC BY-SA 4.0 International",
+        "CC-BY-SA-4.0",
+        Confidence::High,
+    ),
+    (
+        "Creative Commons Attribution 4.0",
+        "CC-BY-4.0",
+        Confidence::High,
+    ),
+    (
+        "Creative Commons Attribution-ShareAlike 3.0",
+        "CC-BY-SA-3.0",
+        Confidence::High,
+    ),
+    (
+        "Creative Commons Attribution-ShareAlike 4.0",
+        "CC-BY-SA-4.0",
+        Confidence::High,
+    ),
+    ("Modified BSD", "BSD-3-Clause", Confidence::High),
+    ("New BSD", "BSD-3-Clause", Confidence::High),
+    ("Simplified BSD", "BSD-2-Clause", Confidence::High),
+    ("GPL", "GPL-3.0-or-later", Confidence::Medium),
+    ("LGPL", "LGPL-3.0-or-later", Confidence::Medium),
+    ("AGPL", "AGPL-3.0-or-later", Confidence::Medium),
+    ("GFDL", "GFDL-1.3-or-later", Confidence::Medium),
+    ("CERN OHL", "CERN-OHL-1.2", Confidence::Medium),
+    ("CERN OHL v2", "CERN-OHL-S-2.0", Confidence::Medium),
+    ("CC BY", "CC-BY-4.0", Confidence::Medium),
+    ("CC BY-SA", "CC-BY-SA-4.0", Confidence::Medium),
+    ("CC BY-NC", "CC-BY-NC-4.0", Confidence::Medium),
+    ("CC BY-NC-SA", "CC-BY-NC-SA-4.0", Confidence::Medium),
+    ("CC BY-ND", "CC-BY-ND-4.0", Confidence::Medium),
+    ("CC BY-NC-ND", "CC-BY-NC-ND-4.0", Confidence::Medium),
+    (
+        "Creative Commons Attribution",
+        "CC-BY-4.0",
+        Confidence::Medium,
+    ),
+    (
+        "Creative Commons Attribution-ShareAlike",
+        "CC-BY-SA-4.0",
+        Confidence::Medium,
+    ),
+    ("Apache", "Apache-2.0", Confidence::Medium),
+    ("BSD", "BSD-3-Clause", Confidence::Medium),
+    ("MPL", "MPL-2.0", Confidence::Medium),
+    ("Solderpad 0.51", "SHL-0.51", Confidence::High),
+    ("Solderpad 2.1", "SHL-2.1", Confidence::High),
+    ("Solderpad", "SHL-2.1", Confidence::Medium),
+];
+
+/// Words that carry no meaning when comparing license names
+const FILLER_WORDS: &[&str] = &["v", "version", "license", "licence", "the", "gnu"];
+
+/// Reduces a license name to a simplified form,
+/// in which different spellings of the same name are (mostly) equal.
+fn simplify(name: &str) -> String {
+    let lower = name.to_lowercase().replace('+', " or later ");
+    let mut tokens: Vec<String> = Vec::new();
+    let mut prev_numeric = false;
+    for word in lower.split(|chr: char| !chr.is_alphanumeric()) {
+        let mut chars = word.chars().peekable();
+        while let Some(&first) = chars.peek() {
+            let numeric = first.is_ascii_digit();
+            let mut token = String::new();
+            while let Some(&chr) = chars.peek() {
+                if chr.is_ascii_digit() != numeric {
+                    break;
+                }
+                token.push(chr);
+                chars.next();
+            }
+            // "3.0" == "3", but "2.1" != "2"
+            let zero_minor = numeric && prev_numeric && token.chars().all(|chr| chr == '0');
+            let filler = !numeric && FILLER_WORDS.contains(&token.as_str());
+            if zero_minor || filler {
+                continue;
+            }
+            // "GPLv3" == "GPL v3" == "GPL 3"
+            if numeric
+                && !prev_numeric
+                && let Some(prev) = tokens.last_mut()
+                && prev.len() > 1
+                && prev.ends_with('v')
+            {
+                prev.pop();
+            }
+            prev_numeric = numeric;
+            tokens.push(token);
+        }
+    }
+    tokens.concat()
+}
+
+/// The digits of a (simplified) license name, e.g. "21" for "lgpl21only".
+fn version_digits(name: &str) -> String {
+    name.chars().filter(char::is_ascii_digit).collect()
+}
+
+/// All the names we know, in simplified form,
+/// together with the SPDX ID they map to,
+/// and how sure we are about that when they match exactly.
+fn known_names() -> &'static [(String, &'static str, Confidence)] {
+    static KNOWN_NAMES: LazyLock<Vec<(String, &'static str, Confidence)>> =
+        LazyLock::new(simplify_known_names);
+    &KNOWN_NAMES
+}
+
+fn simplify_known_names() -> Vec<(String, &'static str, Confidence)> {
+    let spdx_names = spdx::identifiers::LICENSES
+        .iter()
+        .filter(|(id, _, _)| spdx::license_id(id).is_some_and(|lic| !lic.is_deprecated()))
+        .flat_map(|(id, full_name, _)| {
+            [
+                (simplify(id), *id, Confidence::High),
+                (simplify(full_name), *id, Confidence::High),
+            ]
+        });
+    let aliases = ALIASES
+        .iter()
+        .map(|(alias, id, confidence)| (simplify(alias), *id, *confidence));
+    spdx_names.chain(aliases).collect()
+}
+
+/// Maps a license name to an SPDX expression.
+/// Valid SPDX expressions are returned as they are.
+/// Everything else is looked up in our table of SPDX IDs, names and aliases,
+/// first exactly, then fuzzily (in simplified form).
+/// If nothing matches,
+/// the name is turned into a `LicenseRef-`.
+pub fn to_spdx(license: &str) -> Mapping {
+    if validate_spdx_expr(license, false).is_ok() {
+        return Mapping {
+            expression: license.to_owned(),
+            confidence: Confidence::Exact,
+        };
+    }
+    let simplified = simplify(license);
+    let exact = known_names()
+        .iter()
+        .filter(|(name, _, _)| *name == simplified)
+        .max_by_key(|(_, _, confidence)| *confidence);
+    if let Some((_, id, confidence)) = exact {
+        return Mapping {
+            expression: (*id).to_owned(),
+            confidence: *confidence,
+        };
+    }
+    let fuzzy = known_names()
+        .iter()
+        // never guess a different version
+        .filter(|(name, _, _)| version_digits(name) == version_digits(&simplified))
+        .map(|(name, id, _)| (strsim::normalized_levenshtein(name, &simplified), *id))
+        .filter(|(similarity, _)| *similarity >= MIN_SIMILARITY)
+        .max_by(|(sim_a, _), (sim_b, _)| sim_a.total_cmp(sim_b));
+    if let Some((similarity, id)) = fuzzy {
+        log::debug!(
+            "License '{license}' fuzzily matched '{id}' ({:.0}% similar)",
+            similarity * 100.0
+        );
+        return Mapping {
+            expression: id.to_owned(),
+            confidence: Confidence::Low,
+        };
+    }
+    let id_chars: String = license
+        .trim()
+        .chars()
+        .map(|chr| {
+            if chr.is_ascii_alphanumeric() || chr == '-' || chr == '.' {
+                chr
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    Mapping {
+        expression: format!("LicenseRef-{id_chars}"),
+        confidence: Confidence::None,
+    }
+}
@@ -2,6 +2,8 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+pub mod alias;
+
 use std::fmt;
 
 /// An owned/no-lifetimes transcription of `Vec<&spdx::expression::ExpressionReq>`
@@ -82,11 +84,45 @@ pub fn validate_spdx_expr(expr: &str, require_osi_approval: bool) -> Result<(),
     Ok(())
 }
 
+/// Returns the IDs of the licenses in the given SPDX expression,
+/// if it is a single license or a choice between licenses (`OR`),
+/// each of them listed by SPDX and used without an exception.
+/// This is what a list of licenses means in CFF and `CodeMeta`.
+/// GNU licenses are returned with their ID as written
+/// (e.g. `GPL-3.0-or-later`);
+/// other "or later" licenses (`+`) have no SPDX ID.
+pub fn spdx_license_alternatives(expr: &str) -> Option<Vec<&'static str>> {
+    let spdx_expr = spdx::Expression::parse(expr).ok()?;
+    if spdx_expr.iter().any(|node| {
+        matches!(
+            node,
+            spdx::expression::ExprNode::Op(spdx::expression::Operator::And)
+        )
+    }) {
+        return None;
+    }
+    spdx_expr
+        .requirements()
+        .map(|req| match &req.req.license {
+            spdx::LicenseItem::Spdx { id, or_later }
+                if req.req.exception.is_none() && (id.is_gnu() || !or_later) =>
+            {
+                // NOTE The parser reduces GNU licenses to their deprecated root ID
+                //      (e.g. `GPL-3.0`), so we use the ID as written instead.
+                let start = usize::try_from(req.span.start).ok()?;
+                let end = usize::try_from(req.span.end).ok()?;
+                expr.get(start..end)
+                    .and_then(spdx::license_id)
+                    .map(|written_id| written_id.name)
+            }
+            spdx::LicenseItem::Spdx { .. } | spdx::LicenseItem::Other { .. } => None,
+        })
+        .collect()
+}
+
+/// Returns the SPDX expression for a license name,
+/// see [`alias::to_spdx`].
 // TODO The return could be a Cow, but we do not (yet) need it.
 pub fn ensure_spdx_license_id(license_id: &str) -> String {
-    if validate_spdx_expr(license_id, false).is_ok() {
-        license_id.to_owned()
-    } else {
-        format!("LicenseRef-{license_id}")
-    }
+    alias::to_spdx(license_id).expression
 }
@@ -10,12 +10,15 @@ use simplelog::{
     WriteLogger,
 };
 
-pub fn init(file: Option<&Path>, level: (LevelFilter, LevelFilter)) {
+/// Sets up logging to the terminal, and optionally to a file.
+/// `terminal_mode` should be [`TerminalMode::Stderr`]
+/// when stdout is used for data.
+pub fn init(file: Option<&Path>, level: (LevelFilter, LevelFilter), terminal_mode: TerminalMode) {
     let mut loggers: Vec<Box<(dyn SharedLogger + 'static)>> = vec![TermLogger::new(
         // LevelFilter::Info,
         level.0,
         Config::default(),
-        TerminalMode::Mixed,
+        terminal_mode,
         ColorChoice::Auto,
     )];
     if let Some(file_path) = file {
@@ -2,6 +2,7 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+mod batch;
 mod cli;
 mod conversion;
 mod dir;
@@ -13,6 +14,8 @@ mod license;
 mod logger;
 mod macros;
 mod oxrl;
+mod preview;
+mod sanitize;
 mod validation;
 
 use std::{
@@ -20,12 +23,19 @@ use std::{
     error::Error,
     ffi::OsStr,
     fs,
+    io::{self, Read, Write},
     path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
 
+use batch::Outcome;
 use clap::Command;
-use formats::{v1, v2};
+use formats::{citation, oshwa, rdf, v1, v2};
 use log::LevelFilter;
+use preview::Preview;
+use regex::Regex;
+use simplelog::TerminalMode;
 
 macro_rules! main_err {
     ($msg:expr) => {
@@ -53,68 +63,450 @@ where
     Some(parent.to_owned())
 }
 
-#[allow(clippy::fn_params_excessive_bools)]
-fn convert<IP, OP>(
+/// Whether the given path denotes stdin or stdout,
+/// rather than an actual file.
+fn is_std_stream<P>(path: P) -> bool
+where
+    P: AsRef<Path>,
+{
+    path.as_ref().as_os_str() == cli::STD_STREAM
+}
+
+/// The manifest formats we can convert between.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Format {
+    /// OKH v1 (YAML)
+    V1,
+    /// OKH LOSH (TOML)
+    V2,
+    /// OKH LOSH as RDF (Turtle)
+    Rdf,
+    /// OKH LOSH as plain JSON
+    Json,
+    /// OKH LOSH as JSON-LD
+    JsonLd,
+    /// YAML as produced by the LOSH krawler
+    Krawler,
+    /// OSHWA certification record (JSON)
+    Oshwa,
+    /// Citation File Format (`CITATION.cff`)
+    Cff,
+    /// `CodeMeta` (`codemeta.json`)
+    CodeMeta,
+}
+
+impl Format {
+    /// Maps a format name as used on the command-line
+    /// (see [`cli::FORMAT_NAMES`]) to the format.
+    fn by_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "v1" => Self::V1,
+            "losh" => Self::V2,
+            "rdf" => Self::Rdf,
+            "json" => Self::Json,
+            "jsonld" => Self::JsonLd,
+            "krawler" => Self::Krawler,
+            "oshwa" => Self::Oshwa,
+            "cff" => Self::Cff,
+            "codemeta" => Self::CodeMeta,
+            _ => return None,
+        })
+    }
+
+    /// Figures out the format of a manifest file,
+    /// judging by its file extension,
+    /// or in the case of `CodeMeta`, its file name.
+    /// Krawler YAML and OSHWA certifications are never detected this way,
+    /// as they share their extensions with other formats.
+    fn by_ext<P>(file_path: P) -> Option<Self>
+    where
+        P: AsRef<Path>,
+    {
+        if file_path
+            .as_ref()
+            .file_name()
+            .and_then(OsStr::to_str)
+            .is_some_and(|name| Self::CodeMeta.file_matcher().is_match(name))
+        {
+            return Some(Self::CodeMeta);
+        }
+        let ext = file_path.as_ref().extension().and_then(OsStr::to_str)?;
+        [
+            Self::V1,
+            Self::V2,
+            Self::Rdf,
+            Self::Json,
+            Self::JsonLd,
+            Self::Cff,
+        ]
+        .into_iter()
+        .find(|format| format.ext_matcher().is_match(ext))
+    }
+
+    /// The file extension we use when writing this format
+    const fn ext(self) -> &'static str {
+        match self {
+            Self::V1 | Self::Krawler => "yml",
+            Self::V2 => "toml",
+            Self::Rdf => "ttl",
+            Self::Json | Self::Oshwa | Self::CodeMeta => "json",
+            Self::Cff => "cff",
+            Self::JsonLd => "jsonld",
+        }
+    }
+
+    fn ext_matcher(self) -> &'static Regex {
+        match self {
+            Self::V1 | Self::Krawler => v1::Okh::ext_matcher(),
+            Self::V2 => v2::Okh::ext_matcher(),
+            Self::Rdf => rdf::ext_matcher(),
+            Self::Json | Self::CodeMeta => v2::Okh::json_ext_matcher(),
+            Self::JsonLd => v2::Okh::jsonld_ext_matcher(),
+            Self::Oshwa => oshwa::Certification::ext_matcher(),
+            Self::Cff => citation::Cff::ext_matcher(),
+        }
+    }
+
+    fn file_matcher(self) -> &'static Regex {
+        match self {
+            Self::V1 => v1::Okh::file_matcher(),
+            // Crawled files have arbitrary names
+            Self::Krawler => v1::Okh::ext_matcher(),
+            Self::V2 => v2::Okh::file_matcher(),
+            Self::Rdf => rdf::file_matcher(),
+            Self::Json => v2::Okh::json_file_matcher(),
+            Self::JsonLd => v2::Okh::jsonld_file_matcher(),
+            Self::Oshwa => oshwa::Certification::file_matcher(),
+            Self::Cff => citation::Cff::file_matcher(),
+            Self::CodeMeta => citation::CodeMeta::file_matcher(),
+        }
+    }
+
+    /// Parses content of this format into a data tree,
+    /// as required for a semantic diff.
+    fn to_value(self, content: &str) -> Option<serde_json::Value> {
+        if self == Self::V2 {
+            v2::Okh::from_toml(content)
+                .ok()
+                .and_then(|okh| serde_json::to_value(okh).ok())
+        } else if self == Self::Rdf {
+            None
+        } else if self.ext() == "json" || self == Self::JsonLd {
+            serde_json::from_str(content).ok()
+        } else {
+            serde_yaml::from_str(content).ok()
+        }
+    }
+
+    /// The format we convert to,
+    /// if not specified otherwise.
+    const fn default_target(self) -> Self {
+        match self {
+            Self::V1
+            | Self::Rdf
+            | Self::Json
+            | Self::JsonLd
+            | Self::Krawler
+            | Self::Oshwa
+            | Self::Cff
+            | Self::CodeMeta => Self::V2,
+            Self::V2 => Self::V1,
+        }
+    }
+}
+
+/// Converts the manifest read from the first argument,
+/// writing the result to the third argument.
+/// The second argument is the path of the input manifest,
+/// if it was read from a file.
+type Converter = fn(
+    &mut dyn Read,
+    Option<&Path>,
+    &mut dyn Write,
+    &conversion::Options,
+) -> Result<conversion::report::Report, conversion::Error>;
+
+/// Returns the function to convert a single manifest from one format to an other,
+/// if that conversion is supported.
+fn converter(from: Format, to: Format) -> Option<Converter> {
+    match (from, to) {
+        (Format::V1, Format::V2) => Some(|input, manifest_file, output, options| {
+            conversion::v1_to_v2::convert_io(input, manifest_file, output, options)
+        }),
+        (Format::V2, Format::V1) => {
+            Some(|input, _, output, _options| conversion::v2_to_v1::convert_io(input, output))
+        }
+        (Format::V2, Format::Rdf) => {
+            Some(|input, _, output, _options| conversion::v2_to_rdf::convert_io(input, output))
+        }
+        (Format::Rdf, Format::V2) => {
+            Some(|input, _, output, _options| conversion::rdf_to_v2::convert_io(input, output))
+        }
+        (Format::V2, Format::Json) => {
+            Some(|input, _, output, _options| conversion::v2_json::toml_to_json_io(input, output))
+        }
+        (Format::V2, Format::JsonLd) => {
+            Some(|input, _, output, _options| conversion::v2_json::toml_to_jsonld_io(input, output))
+        }
+        (Format::Json, Format::V2) => {
+            Some(|input, _, output, _options| conversion::v2_json::json_to_toml_io(input, output))
+        }
+        (Format::Krawler, Format::V2) => {
+            Some(|input, _, output, _options| conversion::krawler_to_v2::convert_io(input, output))
+        }
+        (Format::V2, Format::Cff) => Some(|input, _, output, _options| {
+            conversion::v2_to_citation::convert_io_cff(input, output)
+        }),
+        (Format::V2, Format::CodeMeta) => Some(|input, _, output, _options| {
+            conversion::v2_to_citation::convert_io_codemeta(input, output)
+        }),
+        (Format::Oshwa, Format::V2) => Some(|input, _, output, options| {
+            conversion::oshwa_to_v2::convert_io(input, output, options)
+        }),
+        _ => None,
+    }
+}
+
+/// Figures out the output file path in case of a single input file.
+fn file_output_path<IP, OP>(
     input_path: IP,
     output_path: Option<OP>,
-    recursive: bool,
-    cont: bool,
-    overwrite: bool,
-    quiet: bool,
-) -> Result<(), Box<dyn Error>>
+    output_ext: &str,
+) -> Result<PathBuf, Box<dyn Error>>
 where
     IP: AsRef<Path>,
     OP: AsRef<Path>,
 {
-    if input_path.as_ref().is_file() {
-        let output_path_val = if let Some(output_path_val) = output_path {
-            if output_path_val.as_ref().exists() {
-                if output_path_val.as_ref().is_file() {
-                    output_path_val.as_ref().to_path_buf()
-                } else {
-                    main_err!("input is a file, so output would have to be too, but is not");
-                }
-            } else {
-                let out_parent = get_parent(output_path_val.as_ref());
-                if let Some(out_parent_val) = out_parent {
-                    if !out_parent_val.exists() {
-                        main_err!(format!(
-                            "the output file's parent directory '{}' does not exist",
-                            out_parent_val.display()
-                        ));
-                    }
-                } else {
-                    main_err!("failed to determine output file's parent directory");
-                }
+    Ok(if let Some(output_path_val) = output_path {
+        if output_path_val.as_ref().exists() {
+            if output_path_val.as_ref().is_file() {
                 output_path_val.as_ref().to_path_buf()
+            } else {
+                main_err!("input is a file, so output would have to be too, but is not");
             }
         } else {
-            let input_ext = input_path.as_ref().extension().and_then(OsStr::to_str);
-            if let Some(input_ext_val) = input_ext {
-                let input_ext_lower = input_ext_val.to_lowercase();
-                if ["yml", "yaml"].contains(&input_ext_lower.as_str()) {
-                    let mut output_path = input_path.as_ref().to_path_buf();
-                    output_path.set_extension("toml");
-                    output_path
-                } else {
-                    main_err!("input file has an unsupported file extension");
+            let out_parent = get_parent(output_path_val.as_ref());
+            if let Some(out_parent_val) = out_parent {
+                if !out_parent_val.exists() {
+                    main_err!(format!(
+                        "the output file's parent directory '{}' does not exist",
+                        out_parent_val.display()
+                    ));
                 }
             } else {
-                main_err!("input file has a non-unicode file extension");
+                main_err!("failed to determine output file's parent directory");
             }
-        };
+            output_path_val.as_ref().to_path_buf()
+        }
+    } else {
+        let mut output_path = input_path.as_ref().to_path_buf();
+        output_path.set_extension(output_ext);
+        output_path
+    })
+}
 
-        let yaml_file = input_path;
-        let toml_file = output_path_val;
-        if toml_file.exists() && !overwrite {
+/// Converts a single file,
+/// and shows and/or writes the conversion report.
+/// Either path may be [`cli::STD_STREAM`],
+/// denoting stdin or stdout respectively.
+#[allow(clippy::too_many_arguments)]
+fn convert_single(
+    converter: Converter,
+    to: Format,
+    input_file: &Path,
+    output_file: &Path,
+    options: &conversion::Options,
+    preview: Preview,
+    write_report: bool,
+    quiet: bool,
+) -> Result<(), conversion::Error> {
+    log::info!(
+        "Converting '{}' to '{}' ...",
+        input_file.display(),
+        output_file.display()
+    );
+    let mut output = Vec::new();
+    let report = if is_std_stream(input_file) {
+        converter(&mut io::stdin().lock(), None, &mut output, options)?
+    } else {
+        let mut input = fs::File::open(input_file).map_err(formats::ParseError::from)?;
+        converter(&mut input, Some(input_file), &mut output, options)?
+    };
+    if is_std_stream(output_file) {
+        io::stdout()
+            .lock()
+            .write_all(&output)
+            .map_err(formats::SerError::from)?;
+    } else {
+        let mut content = String::from_utf8_lossy(&output).into_owned();
+        if to == Format::V2 && output_file.exists() {
+            // keeps manual comments and formatting of the existing manifest
+            let existing = fs::read_to_string(output_file).map_err(formats::SerError::from)?;
+            content = formats::toml_update::update(&existing, &content)?;
+        }
+        preview
+            .write(output_file, &content, |written| to.to_value(written))
+            .map_err(formats::SerError::from)?;
+    }
+    if !quiet {
+        log::info!("{report}");
+    }
+    if write_report {
+        if is_std_stream(output_file) {
+            log::warn!("Not writing a conversion report, because the output goes to stdout");
+        } else if preview.is_active() {
+            log::info!("Not writing a conversion report, because this is only a preview");
+        } else {
+            let report_file = output_file.with_extension(conversion::report::FILE_EXT);
             log::info!(
-                "Skipping conversion of '{}' to '{}', because the target file already exists (see --{})",
-                yaml_file.as_ref().display(),
-                toml_file.display(),
-                cli::A_L_OVERWRITE
+                "Writing conversion report to '{}' ...",
+                report_file.display()
             );
-        } else {
-            conversion::v1_to_v2::convert_file(yaml_file, &toml_file)?;
+            report.to_json_file(report_file)?;
+        }
+    }
+    Ok(())
+}
+
+/// Converts a single file found in an input dir,
+/// creating the output file's parent dir if required.
+#[allow(clippy::too_many_arguments)]
+fn convert_dir_entry(
+    converter: Converter,
+    to: Format,
+    input_file: &Path,
+    output_file: &Path,
+    options: &conversion::Options,
+    preview: Preview,
+    write_report: bool,
+    quiet: bool,
+) -> Outcome<conversion::Error> {
+    let res = output_file
+        .parent()
+        .filter(|_| !preview.is_active())
+        .map_or(Ok(()), fs::create_dir_all)
+        .map_err(|err| conversion::Error::from(formats::SerError::from(err)))
+        .and_then(|()| {
+            convert_single(
+                converter,
+                to,
+                input_file,
+                output_file,
+                options,
+                preview,
+                write_report,
+                quiet,
+            )
+        });
+    match res {
+        Ok(()) => Outcome::Done,
+        Err(err) => {
+            log::warn!("File: '{}'\n{}", input_file.display(), &err);
+            Outcome::Failed(err)
+        }
+    }
+}
+
+/// Whether to skip the conversion, because the output file already exists.
+/// When previewing, existing files are never skipped,
+/// as they are not written to anyway.
+fn skip_existing(input_file: &Path, output_file: &Path, overwrite: bool, preview: Preview) -> bool {
+    let skip = output_file.exists() && !overwrite && !preview.is_active();
+    if skip {
+        log::info!(
+            "Skipping conversion of '{}' to '{}', because the target file already exists (see --{})",
+            input_file.display(),
+            output_file.display(),
+            cli::A_L_OVERWRITE
+        );
+    }
+    skip
+}
+
+/// Figures out the source and target formats
+/// in case of a single input file or stdin.
+fn single_formats<IP, OP>(
+    input_path: IP,
+    output_path: Option<OP>,
+    from: Option<Format>,
+    to: Option<Format>,
+) -> Result<(Format, Format), Box<dyn Error>>
+where
+    IP: AsRef<Path>,
+    OP: AsRef<Path>,
+{
+    let from_val = if is_std_stream(&input_path) {
+        let Some(from_val) = from else {
+            main_err!(format!(
+                "reading from stdin requires the input format to be specified (see --{})",
+                cli::A_L_FROM
+            ));
+        };
+        from_val
+    } else {
+        let Some(from_val) = from.or_else(|| Format::by_ext(&input_path)) else {
+            main_err!("input file has an unsupported file extension");
+        };
+        from_val
+    };
+    let to_val = to
+        .or_else(|| {
+            output_path
+                .filter(|output_path_val| !is_std_stream(output_path_val))
+                .and_then(Format::by_ext)
+        })
+        .unwrap_or_else(|| from_val.default_target());
+    Ok((from_val, to_val))
+}
+
+#[allow(clippy::fn_params_excessive_bools)]
+#[allow(clippy::too_many_arguments)]
+fn convert<IP, OP>(
+    input_path: IP,
+    output_path: Option<OP>,
+    from: Option<Format>,
+    to: Option<Format>,
+    recursive: bool,
+    cont: bool,
+    jobs: usize,
+    overwrite: bool,
+    options: &conversion::Options,
+    preview: Preview,
+    write_report: bool,
+    quiet: bool,
+) -> Result<(), Box<dyn Error>>
+where
+    IP: AsRef<Path>,
+    OP: AsRef<Path>,
+{
+    let from_stdin = is_std_stream(&input_path);
+    if from_stdin || input_path.as_ref().is_file() {
+        let (from_val, to_val) = single_formats(&input_path, output_path.as_ref(), from, to)?;
+        let Some(converter) = converter(from_val, to_val) else {
+            main_err!(format!(
+                "conversion from {from_val:?} to {to_val:?} is not supported"
+            ));
+        };
+        let output_path_val = match output_path {
+            Some(output_path_val) if is_std_stream(&output_path_val) => {
+                PathBuf::from(cli::STD_STREAM)
+            }
+            None if from_stdin => PathBuf::from(cli::STD_STREAM),
+            _ => file_output_path(&input_path, output_path, to_val.ext())?,
+        };
+        if is_std_stream(&output_path_val)
+            || !skip_existing(input_path.as_ref(), &output_path_val, overwrite, preview)
+        {
+            convert_single(
+                converter,
+                to_val,
+                input_path.as_ref(),
+                &output_path_val,
+                options,
+                preview,
+                write_report,
+                quiet,
+            )?;
         }
         Ok(())
     } else if input_path.as_ref().is_dir() {
@@ -129,53 +521,239 @@ where
             None => input_path.as_ref().to_path_buf(),
         };
 
-        let mut total_res = Ok(());
-        let file_matcher = v1::Okh::file_matcher();
-        for yaml_file in dir::iter_exts(dir::walker(&input_path, recursive), v1::Okh::ext_matcher())
-        {
-            if let Some(yaml_file_name) = yaml_file.file_name() {
-                if !file_matcher.is_match(&yaml_file_name.to_string_lossy()) {
-                    continue;
-                }
-                let mut toml_file = output_path_val.join(yaml_file.strip_prefix(&input_path)?);
-                toml_file.set_extension("toml");
-                fs::create_dir_all(toml_file.parent().unwrap())?;
-                if toml_file.exists() && !overwrite {
-                    log::info!(
-                        "Skipping conversion of '{}' to '{}', because the target file already exists (see --{})",
-                        yaml_file.display(),
-                        toml_file.display(),
-                        cli::A_L_OVERWRITE
-                    );
-                    continue;
-                }
-                let res = conversion::v1_to_v2::convert_file(&yaml_file, &toml_file);
-                if let Err(err) = res {
-                    log::warn!("File: '{}'\n{}", yaml_file.display(), &err);
-                    total_res = Err(err); // TODO FIXME We need a simple "Not all succeeded" indicator error here!
-                    if !cont {
-                        break;
-                    }
-                }
+        let from_val = from.unwrap_or(Format::V1);
+        let to_val = to.unwrap_or_else(|| from_val.default_target());
+        let Some(converter) = converter(from_val, to_val) else {
+            main_err!(format!(
+                "conversion from {from_val:?} to {to_val:?} is not supported"
+            ));
+        };
+        let output_ext = to_val.ext();
+        let mut files = Vec::new();
+        for input_file in manifest_files(
+            &input_path,
+            recursive,
+            from_val.ext_matcher(),
+            from_val.file_matcher(),
+        ) {
+            let mut output_file = output_path_val.join(input_file.strip_prefix(&input_path)?);
+            output_file.set_extension(output_ext);
+            files.push((input_file, output_file));
+        }
+        let outcomes = batch::run(&files, jobs, !cont, |(input_file, output_file)| {
+            if skip_existing(input_file, output_file, overwrite, preview) {
+                return Outcome::Skipped;
             }
+            convert_dir_entry(
+                converter,
+                to_val,
+                input_file,
+                output_file,
+                options,
+                preview,
+                write_report,
+                quiet,
+            )
+        })?;
+        let mut summary = batch::Summary::new("converted", true);
+        for ((input_file, _), outcome) in &outcomes {
+            summary.add(input_file, outcome, |err| err.into());
+        }
+        log::info!("{summary}");
+        let num_failed = summary.num_failed();
+        if num_failed > 0 {
+            main_err!(format!(
+                "{num_failed} of {} files failed to convert",
+                outcomes.len()
+            ));
         }
-        Ok(total_res?)
+        Ok(())
     } else {
         main_err!("input is neither a file nor a dir; do not know what to do");
     }
 }
 
+/// Lists the manifest files within a directory,
+/// sorted by path.
+fn manifest_files<IP>(
+    input_path: IP,
+    recursive: bool,
+    ext_matcher: &Regex,
+    file_matcher: &Regex,
+) -> Vec<PathBuf>
+where
+    IP: AsRef<Path>,
+{
+    let mut files: Vec<PathBuf> = dir::iter_exts(dir::walker(&input_path, recursive), ext_matcher)
+        .filter(|input_file| {
+            input_file.file_name().is_some_and(|input_file_name| {
+                file_matcher.is_match(&input_file_name.to_string_lossy())
+            })
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// The checks beyond the JSON schema are only implemented for OKH LOSH.
+fn warn_unsupported_checks(okhv1: bool, options: &validation::Options) {
+    if okhv1 && options.check_files {
+        log::warn!(
+            "Checking file references (--{}) is only supported for OKH LOSH manifests",
+            cli::A_L_CHECK_FILES
+        );
+    }
+    if okhv1 && options.url_checker.is_some() {
+        log::warn!(
+            "Checking URLs (--{}) is only supported for OKH LOSH manifests",
+            cli::A_L_CHECK_URLS
+        );
+    }
+}
+
+/// Validates a manifest read from stdin.
+fn validate_stdin(
+    from: Option<Format>,
+    options: &validation::Options,
+    report: &mut validation::report::Report,
+) -> Result<(), Box<dyn Error>> {
+    let Some(from_val) = from else {
+        main_err!(format!(
+            "reading from stdin requires the input format to be specified (see --{})",
+            cli::A_L_FROM
+        ));
+    };
+    if options.check_files || options.url_checker.is_some() {
+        log::warn!(
+            "Not checking file references (--{}) nor URLs (--{}), as the manifest is read from stdin",
+            cli::A_L_CHECK_FILES,
+            cli::A_L_CHECK_URLS
+        );
+    }
+    let mut manifest = String::new();
+    io::stdin().lock().read_to_string(&mut manifest)?;
+    let res = if from_val == Format::V1 {
+        validation::okh_v1_yaml_str(&manifest)
+    } else if from_val == Format::V2 {
+        validation::okh_losh_toml_str(&manifest)
+    } else if from_val == Format::Json {
+        validation::okh_losh_json_str(&manifest)
+    } else {
+        main_err!(format!("validation of {from_val:?} is not supported"));
+    };
+    if let Err(err) = &res {
+        log_located_failures(Path::new("-"), err);
+    }
+    report.add(Path::new("-"), res.as_ref().err());
+    Ok(res?)
+}
+
+/// Logs each schema validation failure of a manifest
+/// whose location in the file is known,
+/// together with the annotated line it is found on.
+fn log_located_failures(manifest: &Path, err: &validation::Error) {
+    for failure in err.schema_failures() {
+        if let (Some(location), Some(snippet)) = (failure.location, &failure.snippet) {
+            log::error!(
+                "{}:{}:{}: {}\n{snippet}",
+                manifest.display(),
+                location.line,
+                location.column,
+                failure.message
+            );
+        }
+    }
+}
+
+/// Validates all the manifests of the given OKH version in a directory,
+/// in parallel.
+fn validate_dir(
+    input_path: &Path,
+    recursive: bool,
+    jobs: usize,
+    okhv1: bool,
+    options: &validation::Options,
+    report: &mut validation::report::Report,
+) -> Result<(), Box<dyn Error>> {
+    let ext_matcher = if okhv1 {
+        v1::Okh::ext_matcher()
+    } else {
+        v2::Okh::validatable_ext_matcher()
+    };
+    let file_matcher = if okhv1 {
+        v1::Okh::file_matcher()
+    } else {
+        v2::Okh::validatable_file_matcher()
+    };
+    warn_unsupported_checks(okhv1, options);
+    let files = manifest_files(input_path, recursive, ext_matcher, file_matcher);
+    let outcomes = batch::run(&files, jobs, false, |input_file| {
+        let res = if okhv1 {
+            validation::okh_v1_yaml(input_file)
+        } else {
+            validation::okh_losh_toml_or_json(input_file, options)
+        };
+        res.map_or_else(Outcome::Failed, |()| Outcome::Done)
+    })?;
+    let manifests_processed = outcomes.len();
+    let mut summary = batch::Summary::new("valid", false);
+    let mut errors = Vec::new();
+    for (input_file, outcome) in outcomes {
+        summary.add(input_file, &outcome, |err| err.into());
+        match outcome {
+            Outcome::Failed(err) => {
+                log_located_failures(input_file, &err);
+                report.add(input_file, Some(&err));
+                errors.push((input_file.clone(), err));
+            }
+            Outcome::Done | Outcome::Skipped => report.add(input_file, None),
+        }
+    }
+    if manifests_processed > 0 {
+        log::info!("{summary}");
+    }
+    let total_res: Result<(), validation::ErrorCollection> = if !errors.is_empty() {
+        if errors.len() == 1 {
+            Err(errors.into_iter().next().unwrap().into())
+        } else {
+            Err(validation::ErrorCollection { errors })
+        }
+    } else if manifests_processed > 0 {
+        Ok(())
+    } else {
+        Err(validation::ErrorCollection::from((
+            input_path.to_path_buf(),
+            validation::Error::NoManifestsFound,
+        )))
+    };
+    Ok(total_res?)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn validate<IP>(
     input_path: IP,
     recursive: bool,
+    jobs: usize,
     okhv1: Option<bool>,
+    from: Option<Format>,
+    options: &validation::Options,
+    report: &mut validation::report::Report,
     quiet: bool,
 ) -> Result<(), Box<dyn Error>>
 where
     IP: AsRef<Path>,
 {
+    if is_std_stream(&input_path) {
+        return validate_stdin(
+            from.or_else(|| okhv1.map(|okhv1_val| if okhv1_val { Format::V1 } else { Format::V2 })),
+            options,
+            report,
+        );
+    }
+    // NOTE Like for stdin (and in `convert`), --from takes precedence.
+    let okhv1_or_from = from.map(|from_val| from_val == Format::V1).or(okhv1);
     if input_path.as_ref().is_file() {
-        let okhv1_val = match okhv1 {
+        let okhv1_val = match okhv1_or_from {
             Some(okhv1_val) => okhv1_val,
             None => {
                 if v1::Okh::ext_matcher()
@@ -183,7 +761,7 @@ where
                 {
                     // TODO get rid of the unwraps
                     true
-                } else if v2::Okh::ext_matcher()
+                } else if v2::Okh::validatable_ext_matcher()
                     .is_match(input_path.as_ref().extension().unwrap().to_str().unwrap())
                 {
                     // TODO get rid of the unwraps
@@ -193,71 +771,97 @@ where
                 }
             }
         };
-        let validator = if okhv1_val {
-            validation::okh_v1_yaml
+        warn_unsupported_checks(okhv1_val, options);
+        let res = if okhv1_val {
+            validation::okh_v1_yaml(&input_path)
         } else {
-            validation::okh_losh_toml
+            validation::okh_losh_toml_or_json(&input_path, options)
         };
-        Ok(validator(input_path)?)
+        if let Err(err) = &res {
+            log_located_failures(input_path.as_ref(), err);
+        }
+        report.add(input_path.as_ref(), res.as_ref().err());
+        Ok(res?)
     } else if input_path.as_ref().is_dir() {
-        let okhv1_val = okhv1.unwrap_or_else(|| {
+        let okhv1_val = okhv1_or_from.unwrap_or_else(|| {
             panic!(
                 "Input dir specified, but missing an OKH version to scan for, use --{} <OKH-VERSION>",
                 cli::A_L_OKH_VERSION
             )
         });
-        let ext_matcher = if okhv1_val {
-            v1::Okh::ext_matcher()
-        } else {
-            v2::Okh::ext_matcher()
-        };
-        let file_matcher = if okhv1_val {
-            v1::Okh::file_matcher()
-        } else {
-            v2::Okh::file_matcher()
-        };
-        let validator = if okhv1_val {
-            validation::okh_v1_yaml
-        } else {
-            validation::okh_losh_toml
-        };
-        let mut errors = Vec::new();
-        let mut manifests_processed = 0;
-        for input_file in dir::iter_exts(dir::walker(&input_path, recursive), ext_matcher) {
-            if let Some(input_file_name) = input_file.file_name() {
-                if !file_matcher.is_match(&input_file_name.to_string_lossy()) {
-                    continue;
-                }
-                let single_res = validator(input_file.clone());
-                if let Err(err) = single_res {
-                    errors.push((input_file, err));
-                }
-                manifests_processed += 1;
-            }
-        }
-        let total_res: Result<(), validation::ErrorCollection> = if !errors.is_empty() {
-            if errors.len() == 1 {
-                Err(errors.into_iter().next().unwrap().into())
-            } else {
-                Err(validation::ErrorCollection { errors })
-            }
-        } else if manifests_processed > 0 {
-            Ok(())
-        } else {
-            Err(validation::ErrorCollection::from((
-                input_path.as_ref().to_path_buf(),
-                validation::Error::NoManifestsFound,
-            )))
-        };
-        Ok(total_res?)
+        validate_dir(
+            input_path.as_ref(),
+            recursive,
+            jobs,
+            okhv1_val,
+            options,
+            report,
+        )
     } else {
         main_err!("input is neither a file nor a dir; do not know what to do");
     }
 }
 
-fn generate(overwrite: bool, quiet: bool) -> Result<(), Box<dyn Error>> {
+/// Cleans up a dirty OKH v1 manifest,
+/// and lists the fixes applied.
+fn sanitize_manifest(
+    input_path: &Path,
+    output_path: Option<&Path>,
+    in_place: bool,
+    overwrite: bool,
+    quiet: bool,
+) -> Result<(), Box<dyn Error>> {
+    let output_path_val = if in_place {
+        if is_std_stream(input_path) {
+            main_err!(format!(
+                "stdin can not be sanitized in place (see --{})",
+                cli::A_L_IN_PLACE
+            ));
+        }
+        input_path
+    } else {
+        output_path.unwrap_or_else(|| Path::new(cli::STD_STREAM))
+    };
+    if !in_place && !is_std_stream(output_path_val) && output_path_val.exists() && !overwrite {
+        main_err!(format!(
+            "the output file '{}' already exists (see --{})",
+            output_path_val.display(),
+            cli::A_L_OVERWRITE
+        ));
+    }
+    log::info!("Sanitizing '{}' ...", input_path.display());
+    let mut yaml = String::new();
+    if is_std_stream(input_path) {
+        io::stdin().lock().read_to_string(&mut yaml)?;
+    } else {
+        yaml = fs::read_to_string(input_path)?;
+    }
+    let sanitized = sanitize::sanitize_str(&yaml)?;
+    if !quiet {
+        for fix in &sanitized.fixes {
+            log::info!("Fixed {fix}");
+        }
+    }
+    log::info!(
+        "Sanitizing '{}' - done, {} fix(es) applied",
+        input_path.display(),
+        sanitized.fixes.len()
+    );
+    if is_std_stream(output_path_val) {
+        io::stdout().lock().write_all(sanitized.yaml.as_bytes())?;
+    } else {
+        fs::write(output_path_val, sanitized.yaml)?;
+    }
+    Ok(())
+}
+
+fn generate(
+    options: &generation::Options,
+    citation: bool,
+    quiet: bool,
+) -> Result<(), Box<dyn Error>> {
     let proj_root = env::current_dir()?;
-    Ok(generation::okh_losh_toml(&proj_root, overwrite)?)
+    Ok(generation::okh_losh_toml(&proj_root, options, citation)?)
 }
 
 fn print_version_and_exit(quiet: bool) {
@@ -270,13 +874,141 @@ fn print_version_and_exit(quiet: bool) {
     std::process::exit(0);
 }
 
-fn main_inner() -> Result<(), Box<dyn Error>> {
-    if cfg!(debug_assertions) {
-        logger::init(None, (LevelFilter::Trace, LevelFilter::Trace));
+/// Returns the format given by name in the argument with the given ID.
+fn format_arg(args: &clap::ArgMatches, id: &str) -> Option<Format> {
+    args.get_one::<String>(id)
+        .and_then(|name| Format::by_name(name))
+}
+
+/// Returns how to preview writes, as given by the arguments.
+fn preview_arg(args: &clap::ArgMatches) -> Preview {
+    Preview {
+        dry_run: args.get_flag(cli::A_L_DRY_RUN),
+        diff: args.get_flag(cli::A_L_DIFF),
+    }
+}
+
+/// Returns the conversion settings, as given by the arguments.
+fn conversion_options(args: &clap::ArgMatches) -> Result<conversion::Options, Box<dyn Error>> {
+    let rules = args
+        .get_one::<PathBuf>(cli::A_L_MAPPING)
+        .map(conversion::mapping::Rules::from_toml_file)
+        .transpose()?
+        .unwrap_or_default();
+    Ok(conversion::Options {
+        manifest_cache: args.get_one::<PathBuf>(cli::A_L_MANIFEST_CACHE).cloned(),
+        rules,
+        no_git_timestamp: args.get_flag(cli::A_L_NO_GIT_TIMESTAMP),
+        lenient: args.get_flag(cli::A_L_LENIENT),
+    })
+}
+
+/// Runs the validation sub-command,
+/// writing the requested report and URL check cache
+/// no matter whether validation succeeds.
+fn validate_cmd(args: &clap::ArgMatches, quiet: bool) -> Result<(), Box<dyn Error>> {
+    let input_path = args.get_one::<PathBuf>(cli::A_P_INPUT).unwrap();
+    let recursive = args.get_flag(cli::A_L_RECURSIVE);
+    let okhv1 = args
+        .get_one::<String>(cli::A_L_OKH_VERSION)
+        .map(|ver| ver == "v1");
+    let from = format_arg(args, cli::A_L_FROM);
+    let jobs = *args.get_one::<usize>(cli::A_L_JOBS).unwrap();
+    let options = validation_options(args)?;
+    let mut report = validation::report::Report::default();
+    let res = validate(
+        input_path,
+        recursive,
+        jobs,
+        okhv1,
+        from,
+        &options,
+        &mut report,
+        quiet,
+    );
+    if let Some(checker) = &options.url_checker
+        && let Err(err) = checker.save_cache()
+    {
+        log::warn!("Failed to save the URL check cache: {err}");
+    }
+    if let Some(report_file) = args.get_one::<PathBuf>(cli::A_L_REPORT_FILE) {
+        let format = args
+            .get_one::<String>(cli::A_L_REPORT_FORMAT)
+            .unwrap()
+            .parse::<validation::report::Format>()?;
+        report.write(format, report_file)?;
+    }
+    res
+}
+
+/// Returns the validation settings, as given by the arguments.
+fn validation_options(args: &clap::ArgMatches) -> Result<validation::Options, Box<dyn Error>> {
+    let url_checker = if args.get_flag(cli::A_L_CHECK_URLS) {
+        let url_cache = args.get_one::<PathBuf>(cli::A_L_URL_CACHE);
+        let url_options = validation::urls::Options {
+            timeout: Duration::from_secs(*args.get_one::<u64>(cli::A_L_URL_TIMEOUT).unwrap()),
+            jobs: *args.get_one::<usize>(cli::A_L_URL_JOBS).unwrap(),
+            cache_file: if args.get_flag(cli::A_L_NO_URL_CACHE) {
+                None
+            } else {
+                url_cache
+                    .cloned()
+                    .or_else(validation::urls::default_cache_file)
+            },
+            ttl: Duration::from_hours(*args.get_one::<u64>(cli::A_L_URL_CACHE_TTL).unwrap()),
+            unreachable_is_error: args
+                .get_one::<String>(cli::A_L_URL_SEVERITY)
+                .is_some_and(|severity| severity == "error"),
+        };
+        Some(Arc::new(validation::urls::Checker::new(url_options)?))
     } else {
-        logger::init(None, (LevelFilter::Info, LevelFilter::Trace));
+        None
+    };
+    Ok(validation::Options {
+        check_files: args.get_flag(cli::A_L_CHECK_FILES),
+        url_checker,
+    })
+}
+
+/// Returns the generation settings, as given by the arguments.
+fn generation_options(args: &clap::ArgMatches) -> Result<generation::Options, Box<dyn Error>> {
+    let ownership = args
+        .get_one::<PathBuf>(cli::A_L_OWNERSHIP)
+        .map(generation::ownership::Ownership::from_toml_file)
+        .transpose()?
+        .unwrap_or_default();
+    Ok(generation::Options {
+        overwrite: args.get_flag(cli::A_L_OVERWRITE),
+        update: args.get_flag(cli::A_L_UPDATE),
+        ownership,
+        preview: preview_arg(args),
+    })
+}
+
+/// Whether the manifest data is written to stdout,
+/// in which case we must not log to it.
+fn writes_to_stdout(args: &clap::ArgMatches) -> bool {
+    if let Some(sub_com) = args.subcommand_matches(cli::SC_N_SANITIZE) {
+        return !sub_com.get_flag(cli::A_L_IN_PLACE)
+            && sub_com
+                .get_one::<PathBuf>(cli::A_P_OUTPUT)
+                .is_none_or(is_std_stream);
     }
+    args.subcommand_matches(cli::SC_N_CONVERT)
+        .is_some_and(|sub_com| {
+            let output_path = sub_com.get_one::<PathBuf>(cli::A_P_OUTPUT);
+            output_path.map_or_else(
+                || {
+                    sub_com
+                        .get_one::<PathBuf>(cli::A_P_INPUT)
+                        .is_some_and(is_std_stream)
+                },
+                is_std_stream,
+            )
+        })
+}
 
+fn main_inner() -> Result<(), Box<dyn Error>> {
     let arg_matcher = cli::arg_matcher();
     let sub_command_names: Vec<String> = arg_matcher
         .get_subcommands()
@@ -284,6 +1016,21 @@ fn main_inner() -> Result<(), Box<dyn Error>> {
         .map(ToOwned::to_owned)
         .collect();
     let args = &arg_matcher.get_matches();
+
+    let terminal_mode = if writes_to_stdout(args) {
+        TerminalMode::Stderr
+    } else {
+        TerminalMode::Mixed
+    };
+    if cfg!(debug_assertions) {
+        logger::init(
+            None,
+            (LevelFilter::Trace, LevelFilter::Trace),
+            terminal_mode,
+        );
+    } else {
+        logger::init(None, (LevelFilter::Info, LevelFilter::Trace), terminal_mode);
+    }
     let quiet = args.get_flag(cli::A_L_QUIET);
     let version = args.get_flag(cli::A_L_VERSION);
     if version {
@@ -302,21 +1049,46 @@ fn main_inner() -> Result<(), Box<dyn Error>> {
             if sub_com_name == cli::SC_N_CONVERT {
                 let input_path = sub_com.get_one::<PathBuf>(cli::A_P_INPUT).unwrap();
                 let output_path = sub_com.get_one::<PathBuf>(cli::A_P_OUTPUT);
+                let from = format_arg(sub_com, cli::A_L_FROM)
+                    .or_else(|| format_arg(sub_com, cli::A_L_OKH_VERSION));
+                let to = format_arg(sub_com, cli::A_L_TO);
                 let recursive = sub_com.get_flag(cli::A_L_RECURSIVE);
                 let cont = sub_com.get_flag(cli::A_L_CONTINUE_ON_ERROR);
+                let jobs = *sub_com.get_one::<usize>(cli::A_L_JOBS).unwrap();
                 let overwrite = sub_com.get_flag(cli::A_L_OVERWRITE);
-                convert(input_path, output_path, recursive, cont, overwrite, quiet)?;
+                let write_report = sub_com.get_flag(cli::A_L_REPORT);
+                let options = conversion_options(sub_com)?;
+                convert(
+                    input_path,
+                    output_path,
+                    from,
+                    to,
+                    recursive,
+                    cont,
+                    jobs,
+                    overwrite,
+                    &options,
+                    preview_arg(sub_com),
+                    write_report,
+                    quiet,
+                )?;
             } else if sub_com_name == cli::SC_N_VALIDATE {
-                let input_path = sub_com.get_one::<PathBuf>(cli::A_P_INPUT).unwrap();
-                let recursive = sub_com.get_flag(cli::A_L_RECURSIVE);
-                let okhv1 = sub_com
-                    .get_one::<String>(cli::A_L_OKH_VERSION)
-                    .map(|ver| ver == "v1");
-                validate(input_path, recursive, okhv1, quiet)?;
+                validate_cmd(sub_com, quiet)?;
                 log::info!("Valid!");
             } else if sub_com_name == cli::SC_N_GENERATE {
-                let overwrite = sub_com.get_flag(cli::A_L_OVERWRITE);
-                generate(overwrite, quiet)?;
+                let options = generation_options(sub_com)?;
+                let citation = sub_com.get_flag(cli::A_L_CITATION);
+                generate(&options, citation, quiet)?;
+            } else if sub_com_name == cli::SC_N_SANITIZE {
+                let input_path = sub_com.get_one::<PathBuf>(cli::A_P_INPUT).unwrap();
+                let output_path = sub_com.get_one::<PathBuf>(cli::A_P_OUTPUT);
+                sanitize_manifest(
+                    input_path,
+                    output_path.map(PathBuf::as_path),
+                    sub_com.get_flag(cli::A_L_IN_PLACE),
+                    sub_com.get_flag(cli::A_L_OVERWRITE),
+                    quiet,
+                )?;
             } else {
                 main_err!(format!("Sub-command not implemented: '{sub_com_name}'"));
             }
@@ -0,0 +1,184 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Shows what would be written to a file, instead of writing it
+//! (see `--dry-run` and `--diff`).
+//! The diff is semantic: it compares the parsed data field by field,
+//! rather than the text line by line.
+
+use std::{
+    fmt::{self, Display},
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use serde_json::Value;
+
+/// Whether and how to preview writes instead of doing them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Preview {
+    /// Show the content that would be written
+    pub dry_run: bool,
+    /// Show a field-level diff between the existing and the new content
+    pub diff: bool,
+}
+
+/// A single field-level difference between two data trees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added {
+        path: String,
+        new: Value,
+    },
+    Removed {
+        path: String,
+        old: Value,
+    },
+    Modified {
+        path: String,
+        old: Value,
+        new: Value,
+    },
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { path, new } => write!(f, "+ {path}: {new}"),
+            Self::Removed { path, old } => write!(f, "- {path}: {old}"),
+            Self::Modified { path, old, new } => write!(f, "~ {path}: {old} -> {new}"),
+        }
+    }
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+fn diff_into(path: &str, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            for (key, old_value) in old_map {
+                let key_path = child_path(path, key);
+                match new_map.get(key) {
+                    Some(new_value) => diff_into(&key_path, old_value, new_value, changes),
+                    None => changes.push(Change::Removed {
+                        path: key_path,
+                        old: old_value.clone(),
+                    }),
+                }
+            }
+            for (key, new_value) in new_map {
+                if !old_map.contains_key(key) {
+                    changes.push(Change::Added {
+                        path: child_path(path, key),
+                        new: new_value.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            for (idx, (old_item, new_item)) in old_items.iter().zip(new_items).enumerate() {
+                diff_into(&format!("{path}[{idx}]"), old_item, new_item, changes);
+            }
+            for (idx, old_item) in old_items.iter().enumerate().skip(new_items.len()) {
+                changes.push(Change::Removed {
+                    path: format!("{path}[{idx}]"),
+                    old: old_item.clone(),
+                });
+            }
+            for (idx, new_item) in new_items.iter().enumerate().skip(old_items.len()) {
+                changes.push(Change::Added {
+                    path: format!("{path}[{idx}]"),
+                    new: new_item.clone(),
+                });
+            }
+        }
+        _ => {
+            if old != new {
+                changes.push(Change::Modified {
+                    path: path.to_owned(),
+                    old: old.clone(),
+                    new: new.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Lists the field-level differences between two data trees.
+/// Object members are compared by key, array items by index.
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_into("", old, new, &mut changes);
+    changes
+}
+
+impl Preview {
+    /// Whether writes should be previewed instead of done
+    pub const fn is_active(self) -> bool {
+        self.dry_run || self.diff
+    }
+
+    /// Writes `content` to `file`, or if previewing,
+    /// shows the content and/or how it differs
+    /// from what is currently stored in `file`.
+    /// `to_value` parses the content into a data tree for the diff;
+    /// it returns `None` if that is not possible.
+    ///
+    /// The preview is rendered first, and then written to stdout at once,
+    /// so the previews of files processed in parallel do not interleave.
+    /// NOTE We must not log while holding the stdout lock,
+    ///      as the logger might wait for stdout too, which deadlocks.
+    ///
+    /// # Errors
+    ///
+    /// If writing to the file or to stdout fails.
+    pub fn write<F>(self, file: &Path, content: &str, to_value: F) -> io::Result<()>
+    where
+        F: Fn(&str) -> Option<Value>,
+    {
+        if !self.is_active() {
+            return fs::write(file, content);
+        }
+        let mut rendered = String::new();
+        if self.dry_run {
+            log::info!("Would write to '{}':", file.display());
+            rendered.push_str(content);
+            if !content.ends_with('\n') {
+                rendered.push('\n');
+            }
+        }
+        if self.diff {
+            let old = if file.exists() {
+                to_value(&fs::read_to_string(file)?)
+            } else {
+                Some(Value::Object(serde_json::Map::new()))
+            };
+            if let (Some(old_val), Some(new_val)) = (old, to_value(content)) {
+                let changes = diff(&old_val, &new_val);
+                if changes.is_empty() {
+                    log::info!("No changes to '{}'.", file.display());
+                } else {
+                    log::info!("Changes to '{}':", file.display());
+                    for change in changes {
+                        rendered.push_str(&change.to_string());
+                        rendered.push('\n');
+                    }
+                }
+            } else {
+                log::warn!(
+                    "Unable to show a diff for '{}', because its content could not be parsed",
+                    file.display()
+                );
+            }
+        }
+        io::stdout().lock().write_all(rendered.as_bytes())
+    }
+}
@@ -0,0 +1,696 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Cleans up dirty OKH v1 YAML manifests, as commonly found in the wild,
+//! avoiding pitfalls like lines left over from the manifest template,
+//! half-filled keys, tab indentation and wrongly typed values.
+//! This replaces the former `run/sanitize-v1-yaml` script.
+//!
+//! Only what prevents the YAML from being parsed is fixed on the text;
+//! everything else is fixed on the YAML tree.
+//! Every fix applied is recorded.
+
+use std::{
+    fmt,
+    io::{Read, Write},
+};
+
+use serde_yaml::{Mapping, Value};
+
+use crate::formats::{ParseError, SerError};
+use crate::macros::rgx;
+
+/// Used where a person is required, but none is given
+const ANONYMOUS: &str = "ANONYMOUS";
+
+/// Used where a version is required, but none is given
+const UNVERSIONED: &str = "UNVERSIONED";
+
+/// Keys with a single string value
+const STRING_KEYS: &[&str] = &[
+    "title",
+    "description",
+    "intended-use",
+    "project-link",
+    "image",
+    "okh-manifest-version",
+    "date-created",
+    "date-updated",
+    "manifest-language",
+    "version",
+    "development-stage",
+    "documentation-home",
+    "archive-download",
+    "documentation-language",
+    "bom",
+    "tools-list",
+    "health-safety-notice",
+];
+
+/// Keys with a boolean value
+const BOOL_KEYS: &[&str] = &["made", "made-independently"];
+
+/// Keys with a list of strings as value
+const STRING_LIST_KEYS: &[&str] = &["keywords", "design-files"];
+
+/// Keys with a single person as value
+const PERSON_KEYS: &[&str] = &["licensor", "manifest-author", "contact"];
+
+/// Keys with a list of persons as value
+const PERSON_LIST_KEYS: &[&str] = &["contributors"];
+
+/// Keys with a list of documents (`title` and `path`) as value
+const DOCUMENT_LIST_KEYS: &[&str] = &[
+    "schematics",
+    "making-instructions",
+    "manufacturing-files",
+    "risk-assessment",
+    "tool-settings",
+    "quality-instructions",
+    "operating-instructions",
+    "maintenance-instructions",
+    "disposal-instructions",
+    "software",
+];
+
+/// The sub-keys of `license`
+const LICENSE_KEYS: &[&str] = &["hardware", "documentation", "software"];
+
+/// Values (compared case-insensitively) that were left over from the template
+const PLACEHOLDERS: &[&str] = &["value", "todo", "tbd", "tba", "xxx", "...", "-"];
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Failed to parse the YAML, even after sanitizing it")]
+    Parse(#[from] ParseError),
+
+    #[error("The manifest is not a YAML mapping (key-value pairs)")]
+    NotAMapping,
+
+    #[error("Failed to serialize the sanitized YAML")]
+    Serialize(#[from] SerError),
+}
+
+/// A single fix applied to a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    /// Where the fix was applied;
+    /// either a line (for fixes on the text)
+    /// or a key path like `licensor.name` (for fixes on the YAML tree)
+    pub location: String,
+    pub description: String,
+}
+
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.description)
+    }
+}
+
+/// The result of sanitizing a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sanitized {
+    pub yaml: String,
+    /// All the fixes that were applied, in order
+    pub fixes: Vec<Fix>,
+}
+
+/// Collects the applied fixes.
+#[derive(Debug, Default)]
+struct Fixes(Vec<Fix>);
+
+impl Fixes {
+    fn add<L: Into<String>, D: Into<String>>(&mut self, location: L, description: D) {
+        let fix = Fix {
+            location: location.into(),
+            description: description.into(),
+        };
+        log::debug!("Sanitizing - {fix}");
+        self.0.push(fix);
+    }
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+fn line_location(line_idx: usize) -> String {
+    format!("line {}", line_idx + 1)
+}
+
+/// Fixes the problems on a single line that always prevent parsing:
+/// tab indentation and unicode replacement characters.
+/// `may_nest` tells whether the line may be nested
+/// below the previous (non-empty) line;
+/// if not, its tab indentation is removed instead of replaced.
+fn sanitize_line(line_idx: usize, line: &str, may_nest: bool, fixes: &mut Fixes) -> String {
+    let mut cleaned = line.to_owned();
+    if cleaned.contains('\u{FFFD}') || cleaned.contains("\\uFFFD") {
+        cleaned = cleaned.replace('\u{FFFD}', "").replace("\\uFFFD", "");
+        fixes.add(
+            line_location(line_idx),
+            "removed unicode replacement character(s)",
+        );
+    }
+    let content = cleaned.trim_start();
+    let indent: String = cleaned
+        .chars()
+        .take_while(|chr| chr.is_whitespace())
+        .collect();
+    if indent.contains('\t') {
+        if may_nest {
+            cleaned = format!("{}{content}", indent.replace('\t', "  "));
+            fixes.add(
+                line_location(line_idx),
+                "replaced tab indentation with spaces",
+            );
+        } else {
+            cleaned.drain(..indent.len());
+            fixes.add(
+                line_location(line_idx),
+                "removed tab indentation of a top-level line",
+            );
+        }
+    }
+    cleaned
+}
+
+/// Fixes the problems on the text that always prevent parsing,
+/// or that are known to lead to a wrong parse.
+fn sanitize_lines(yaml: &str, fixes: &mut Fixes) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut content_started = false;
+    // Whether the previous content line is an indented one,
+    // or a key that starts a nested block
+    let mut may_nest = false;
+    for (line_idx, line) in yaml.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('%') && !trimmed.starts_with("%YAML") && !trimmed.starts_with("%TAG")
+        {
+            // e.g. "%Open know-how manifest 0.1"
+            fixes.add(
+                line_location(line_idx),
+                format!("removed invalid directive '{trimmed}'"),
+            );
+            lines.push(String::new());
+            continue;
+        }
+        if trimmed == "---" && content_started {
+            fixes.add(
+                line_location(line_idx),
+                "removed document separator, as a manifest is a single document",
+            );
+            lines.push(String::new());
+            continue;
+        }
+        let is_content = !trimmed.is_empty() && !trimmed.starts_with('#');
+        if is_content {
+            content_started = true;
+        }
+        let sanitized = sanitize_line(line_idx, line, may_nest, fixes);
+        if is_content {
+            may_nest = sanitized.starts_with(char::is_whitespace)
+                || rgx!(r":\s*(#.*)?$").is_match(&sanitized);
+        }
+        lines.push(sanitized);
+    }
+    lines
+}
+
+/// Tries to fix a single line that prevents parsing.
+/// Returns whether a fix was applied.
+fn fix_unparsable_line(line_idx: usize, lines: &mut [String], fixes: &mut Fixes) -> bool {
+    let Some(line) = lines.get_mut(line_idx) else {
+        return false;
+    };
+    // A value that contains ": " or starts with a reserved character,
+    // like "title: Foo: The Bar" or "user-handle: @foo"
+    if let Some(caps) =
+        rgx!(r"^(\s*(?:-\s+)?[A-Za-z0-9_-]+:)\s+([^'\x22\[{>|&*!#\s].*)$").captures(line)
+        && let (Some(key), Some(value)) = (caps.get(1), caps.get(2))
+        && !value.as_str().contains(" #")
+        && (value.as_str().contains(": ") || value.as_str().starts_with(['@', '`']))
+    {
+        let quoted = format!("{} '{}'", key.as_str(), value.as_str().replace('\'', "''"));
+        fixes.add(
+            line_location(line_idx),
+            "quoted a value containing special characters",
+        );
+        *line = quoted;
+        return true;
+    }
+    // Left-overs like HTML or template instructions
+    if !line.starts_with([' ', '#', '-'])
+        && !line.trim().is_empty()
+        && !rgx!(r"^[^\s:#][^:#]*:(\s|$)").is_match(line)
+    {
+        fixes.add(
+            line_location(line_idx),
+            format!("commented out stray text '{}'", line.trim()),
+        );
+        *line = format!("# {line}");
+        return true;
+    }
+    false
+}
+
+/// Parses the (already sanitized) lines as YAML,
+/// fixing the lines that prevent parsing, as long as possible.
+fn parse_lines(mut lines: Vec<String>, fixes: &mut Fixes) -> Result<Value, Error> {
+    for _ in 0..=lines.len() {
+        let err = match serde_yaml::from_str::<Value>(&lines.join("\n")) {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+        let Some(location) = err.location() else {
+            return Err(ParseError::from(err).into());
+        };
+        // The parser sometimes only notices a problem on the line after
+        let err_line_idx = location.line().saturating_sub(1);
+        let repaired = fix_unparsable_line(err_line_idx, &mut lines, fixes)
+            || err_line_idx
+                .checked_sub(1)
+                .is_some_and(|prev_line_idx| fix_unparsable_line(prev_line_idx, &mut lines, fixes));
+        if !repaired {
+            return Err(ParseError::from(err).into());
+        }
+    }
+    Ok(serde_yaml::from_str::<Value>(&lines.join("\n")).map_err(ParseError::from)?)
+}
+
+/// Returns the text within the brackets of a value like `<jane@example.org>`,
+/// `[value]` or `<name>`.
+fn bracketed(text: &str) -> Option<&str> {
+    let trimmed = text.trim();
+    trimmed
+        .strip_prefix('<')
+        .and_then(|rest| rest.strip_suffix('>'))
+        .or_else(|| {
+            trimmed
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+        })
+}
+
+/// Returns the template placeholder text of a value,
+/// if it is one, like `[value]`, `<name>` or `TODO`.
+/// Bracketed values only count as placeholders
+/// if the brackets hold (nothing but) template words.
+fn placeholder(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => {
+            let trimmed = text.trim();
+            let is_placeholder = PLACEHOLDERS.contains(&trimmed.to_lowercase().as_str())
+                || bracketed(trimmed).is_some_and(|inner| rgx!(r"^[A-Za-z _-]*$").is_match(inner));
+            is_placeholder.then(|| trimmed.to_owned())
+        }
+        // "key: [value]" is a YAML list
+        Value::Sequence(items) => match items.as_slice() {
+            [single] => placeholder(single).map(|text| format!("[{text}]")),
+            _ => None,
+        },
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::Mapping(_) | Value::Tagged(_) => {
+            None
+        }
+    }
+}
+
+/// Whether a value carries no information at all.
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(text) => text.trim().is_empty(),
+        Value::Sequence(items) => items.is_empty(),
+        Value::Mapping(entries) => entries.is_empty(),
+        Value::Bool(_) | Value::Number(_) | Value::Tagged(_) => false,
+    }
+}
+
+/// Removes empty values and template placeholders, recursively.
+/// Brackets around other values (e.g. `<https://example.org>`) are removed.
+fn remove_empty(value: &mut Value, path: &str, fixes: &mut Fixes) {
+    match value {
+        Value::String(text) => {
+            if let Some(inner) = bracketed(text).map(|inner| inner.trim().to_owned()) {
+                fixes.add(path, format!("removed the brackets around '{inner}'"));
+                *text = inner;
+            }
+        }
+        Value::Sequence(items) => {
+            let mut idx = 0;
+            items.retain_mut(|item| {
+                let item_path = format!("{path}[{idx}]");
+                idx += 1;
+                let removal = placeholder(item)
+                    .map(|text| format!("removed template placeholder '{text}'"))
+                    .or_else(|| {
+                        remove_empty(item, &item_path, fixes);
+                        is_empty(item).then(|| "removed empty list item".to_owned())
+                    });
+                removal.is_none_or(|description| {
+                    fixes.add(item_path, description);
+                    false
+                })
+            });
+        }
+        Value::Mapping(entries) => {
+            let mut empty_keys = Vec::new();
+            for (key, entry_value) in entries.iter_mut() {
+                let key_str = key.as_str().unwrap_or_default();
+                let entry_path = child_path(path, key_str);
+                let removal = placeholder(entry_value)
+                    .map(|text| format!("removed template placeholder '{text}'"))
+                    .or_else(|| {
+                        remove_empty(entry_value, &entry_path, fixes);
+                        is_empty(entry_value)
+                            .then(|| "removed half-filled key without a value".to_owned())
+                    });
+                if let Some(description) = removal {
+                    fixes.add(entry_path, description);
+                    empty_keys.push(key.clone());
+                }
+            }
+            entries.retain(|key, _| !empty_keys.contains(key));
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::Tagged(_) => {}
+    }
+}
+
+/// Turns scalars and lists of scalars into a single string.
+fn to_string_value(value: &mut Value, path: &str, fixes: &mut Fixes) {
+    let text = match value {
+        Value::Bool(boolean) => {
+            fixes.add(path, "converted boolean to string");
+            boolean.to_string()
+        }
+        Value::Number(number) => {
+            fixes.add(path, "converted number to string");
+            number.to_string()
+        }
+        Value::Sequence(items) => {
+            let mut texts = Vec::new();
+            for item in items.iter_mut() {
+                to_string_value(item, path, fixes);
+                match item.as_str() {
+                    Some(text) => texts.push(text.to_owned()),
+                    None => return,
+                }
+            }
+            fixes.add(path, "joined list into a single string");
+            texts.join(", ")
+        }
+        Value::Null | Value::String(_) | Value::Mapping(_) | Value::Tagged(_) => return,
+    };
+    *value = Value::String(text);
+}
+
+/// Turns strings like "TRUE", "yes" or "flase" into booleans.
+fn to_bool_value(value: &mut Value, path: &str, fixes: &mut Fixes) {
+    let Some(text) = value.as_str() else {
+        return;
+    };
+    let boolean = match text.trim().to_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" => true,
+        "false" | "flase" | "no" | "n" | "0" => false,
+        _ => return,
+    };
+    fixes.add(path, format!("converted '{text}' to a boolean"));
+    *value = Value::Bool(boolean);
+}
+
+/// Turns a single (comma separated) string into a list of strings.
+fn to_string_list_value(value: &mut Value, path: &str, fixes: &mut Fixes) {
+    if let Some(text) = value.as_str() {
+        let items = text
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| Value::String(item.to_owned()))
+            .collect();
+        fixes.add(path, "split string into a list");
+        *value = Value::Sequence(items);
+    }
+    if let Value::Sequence(items) = value {
+        for (idx, item) in items.iter_mut().enumerate() {
+            to_string_value(item, &format!("{path}[{idx}]"), fixes);
+        }
+    }
+}
+
+/// Wraps a single value into a list.
+fn to_list_value(value: &mut Value, path: &str, fixes: &mut Fixes) {
+    if !value.is_sequence() {
+        fixes.add(path, "wrapped single value into a list");
+        *value = Value::Sequence(vec![value.clone()]);
+    }
+}
+
+/// De-obfuscates e-mail addresses like "jane(at)example(dot)org".
+fn fix_email(value: &mut Value, path: &str, fixes: &mut Fixes) {
+    let Some(email) = value.as_str() else {
+        return;
+    };
+    let with_at = rgx!(r"\s*[\[(]\s*(?i:at)\s*[\])]\s*").replace_all(email, "@");
+    let deobfuscated = rgx!(r"\s*[\[(]\s*(?i:dot)\s*[\])]\s*").replace_all(&with_at, ".");
+    if deobfuscated != email {
+        fixes.add(path, format!("de-obfuscated e-mail '{email}'"));
+        *value = Value::String(deobfuscated.into_owned());
+    }
+}
+
+/// Turns a person given as string ("Jane Doe <jane@example.org>")
+/// into a mapping, and fixes its parts.
+fn to_person_value(value: &mut Value, path: &str, fixes: &mut Fixes) {
+    if let Some(text) = value.as_str() {
+        let mut person = Mapping::new();
+        if let Some(caps) = rgx!(r"^(.*?)\s*<([^>]*)>$").captures(text.trim()) {
+            for (key, part) in [("name", caps.get(1)), ("email", caps.get(2))] {
+                if let Some(part_val) = part.filter(|part_val| !part_val.as_str().is_empty()) {
+                    person.insert(key.into(), part_val.as_str().into());
+                }
+            }
+        } else {
+            person.insert("name".into(), text.trim().into());
+        }
+        fixes.add(path, format!("converted '{text}' into a person"));
+        *value = Value::Mapping(person);
+    }
+    if let Value::Mapping(person) = value {
+        if let Some(email) = person.get_mut("email") {
+            fix_email(email, &child_path(path, "email"), fixes);
+        }
+        if let Some(name) = person.get_mut("name") {
+            to_string_value(name, &child_path(path, "name"), fixes);
+        }
+        if !person.contains_key("name") && !person.contains_key("email") {
+            fixes.add(
+                child_path(path, "name"),
+                format!("set to '{ANONYMOUS}', as neither name nor e-mail were given"),
+            );
+            person.insert("name".into(), ANONYMOUS.into());
+        }
+    }
+}
+
+/// Turns a document given as string into a mapping with a `path`.
+fn to_document_value(value: &mut Value, path: &str, fixes: &mut Fixes) {
+    if let Some(text) = value.as_str() {
+        let mut document = Mapping::new();
+        document.insert("path".into(), text.trim().into());
+        fixes.add(path, "converted string into a document with a path");
+        *value = Value::Mapping(document);
+    }
+}
+
+/// Fixes the `license` mapping.
+fn fix_license(value: &mut Value, fixes: &mut Fixes) {
+    if let Some(text) = value.as_str() {
+        let mut license = Mapping::new();
+        license.insert("hardware".into(), text.into());
+        license.insert("documentation".into(), text.into());
+        fixes.add(
+            "license",
+            "used the single license string for hardware and documentation",
+        );
+        *value = Value::Mapping(license);
+    }
+    if let Value::Mapping(license) = value {
+        for key in LICENSE_KEYS {
+            if let Some(lcse) = license.get_mut(*key) {
+                to_string_value(lcse, &child_path("license", key), fixes);
+            }
+        }
+    }
+}
+
+/// Fixes the types of the values of the known keys.
+fn fix_types(manifest: &mut Mapping, fixes: &mut Fixes) {
+    for (key, value) in manifest.iter_mut() {
+        let Some(key_str) = key.as_str() else {
+            continue;
+        };
+        match key_str {
+            "license" => fix_license(value, fixes),
+            _ if STRING_KEYS.contains(&key_str) => to_string_value(value, key_str, fixes),
+            _ if BOOL_KEYS.contains(&key_str) => to_bool_value(value, key_str, fixes),
+            _ if STRING_LIST_KEYS.contains(&key_str) => {
+                to_string_list_value(value, key_str, fixes);
+            }
+            _ if PERSON_KEYS.contains(&key_str) => to_person_value(value, key_str, fixes),
+            _ if PERSON_LIST_KEYS.contains(&key_str) => {
+                to_list_value(value, key_str, fixes);
+                if let Value::Sequence(items) = value {
+                    for (idx, item) in items.iter_mut().enumerate() {
+                        to_person_value(item, &format!("{key_str}[{idx}]"), fixes);
+                    }
+                }
+            }
+            _ if DOCUMENT_LIST_KEYS.contains(&key_str) => {
+                to_list_value(value, key_str, fixes);
+                if let Value::Sequence(items) = value {
+                    for (idx, item) in items.iter_mut().enumerate() {
+                        to_document_value(item, &format!("{key_str}[{idx}]"), fixes);
+                    }
+                }
+            }
+            // all other keys are of a type that can not be mistaken
+            _ => {}
+        }
+    }
+}
+
+/// Percent-escapes the `%` characters in a URL
+/// that do not start an escape sequence already,
+/// like in `https://example.org/CD4%_System`.
+fn escape_percent(url: &str) -> Option<String> {
+    let mut escaped = String::with_capacity(url.len());
+    let mut changed = false;
+    for (idx, chr) in url.char_indices() {
+        let is_escape = url
+            .get(idx + 1..idx + 3)
+            .is_some_and(|hex| hex.chars().all(|digit| digit.is_ascii_hexdigit()));
+        if chr == '%' && !is_escape {
+            escaped.push_str("%25");
+            changed = true;
+        } else {
+            escaped.push(chr);
+        }
+    }
+    changed.then_some(escaped)
+}
+
+/// Fixes all string values, recursively:
+/// Strips the `./` prefix from paths,
+/// and percent-escapes stray `%` characters in URLs.
+fn fix_strings(value: &mut Value, path: &str, fixes: &mut Fixes) {
+    match value {
+        Value::String(text) => {
+            if text.starts_with("./") && text.len() > 2 {
+                fixes.add(
+                    path,
+                    format!("removed the './' prefix of the path '{text}'"),
+                );
+                text.drain(..2);
+            }
+            if rgx!(r"^[hH][tT][tT][pP][sS]?://").is_match(text)
+                && let Some(escaped) = escape_percent(text)
+            {
+                fixes.add(path, format!("percent-escaped the '%' in the URL '{text}'"));
+                *text = escaped;
+            }
+        }
+        Value::Sequence(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                fix_strings(item, &format!("{path}[{idx}]"), fixes);
+            }
+        }
+        Value::Mapping(entries) => fix_strings_in(entries, path, fixes),
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::Tagged(_) => {}
+    }
+}
+
+/// Fixes all string values within a mapping; see [`fix_strings`].
+fn fix_strings_in(entries: &mut Mapping, path: &str, fixes: &mut Fixes) {
+    for (key, entry_value) in entries.iter_mut() {
+        let entry_path = child_path(path, key.as_str().unwrap_or_default());
+        fix_strings(entry_value, &entry_path, fixes);
+    }
+}
+
+/// Adds the values that OKH LOSH requires,
+/// but are commonly missing in OKH v1 manifests.
+fn add_required(manifest: &mut Mapping, fixes: &mut Fixes) {
+    if !manifest.contains_key("version") {
+        fixes.add("version", format!("set missing version to '{UNVERSIONED}'"));
+        manifest.insert("version".into(), UNVERSIONED.into());
+    }
+    if !manifest.contains_key("licensor") {
+        let mut licensor = Mapping::new();
+        licensor.insert("name".into(), ANONYMOUS.into());
+        fixes.add("licensor", format!("set missing licensor to '{ANONYMOUS}'"));
+        manifest.insert("licensor".into(), Value::Mapping(licensor));
+    }
+}
+
+fn sanitize_tree(manifest: &mut Value, fixes: &mut Fixes) -> Result<(), Error> {
+    remove_empty(manifest, "", fixes);
+    let Value::Mapping(entries) = manifest else {
+        return Err(Error::NotAMapping);
+    };
+    fix_types(entries, fixes);
+    fix_strings_in(entries, "", fixes);
+    add_required(entries, fixes);
+    Ok(())
+}
+
+/// Sanitizes an OKH v1 manifest that was already parsed into a YAML tree.
+///
+/// # Errors
+///
+/// If the manifest is not a mapping.
+pub fn sanitize_value(manifest: &mut Value) -> Result<Vec<Fix>, Error> {
+    let mut fixes = Fixes::default();
+    sanitize_tree(manifest, &mut fixes)?;
+    Ok(fixes.0)
+}
+
+/// Sanitizes an OKH v1 manifest given as YAML string.
+///
+/// # Errors
+///
+/// If the YAML is broken beyond what we are able to fix,
+/// or if the manifest is not a mapping.
+pub fn sanitize_str(yaml: &str) -> Result<Sanitized, Error> {
+    log::debug!("Sanitizing OKH v1 YAML ...");
+    let mut fixes = Fixes::default();
+    let lines = sanitize_lines(yaml, &mut fixes);
+    let mut manifest = parse_lines(lines, &mut fixes)?;
+    sanitize_tree(&mut manifest, &mut fixes)?;
+    let sanitized = serde_yaml::to_string(&manifest).map_err(SerError::from)?;
+    Ok(Sanitized {
+        yaml: sanitized,
+        fixes: fixes.0,
+    })
+}
+
+/// Sanitizes an OKH v1 manifest read from `input`,
+/// writing the result to `output`.
+///
+/// # Errors
+///
+/// If reading or writing fails,
+/// or if sanitizing fails (see [`sanitize_str`]).
+pub fn sanitize_io<R: Read, W: Write>(mut input: R, mut output: W) -> Result<Vec<Fix>, Error> {
+    let mut yaml = String::new();
+    input.read_to_string(&mut yaml).map_err(ParseError::from)?;
+    let sanitized = sanitize_str(&yaml)?;
+    output
+        .write_all(sanitized.yaml.as_bytes())
+        .map_err(SerError::from)?;
+    Ok(sanitized.fixes)
+}
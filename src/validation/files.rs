@@ -0,0 +1,168 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Checks whether the files an OKH LOSH manifest refers to
+//! actually exist within the repository.
+
+use std::{fmt, path::Path};
+
+use relative_path::{RelativePath, RelativePathBuf};
+use serde_json::Value;
+use url::Url;
+
+use crate::formats::spans;
+
+/// What is wrong with a file reference.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// The file does not exist
+    Missing,
+    /// The path leads out of the repository (through `..`)
+    OutsideRepo,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Missing => "does not exist",
+            Self::OutsideRepo => "points outside of the repository",
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("'{field}': '{path}' {problem}")]
+pub struct BrokenReference {
+    /// The path of the property in the manifest,
+    /// for example `part[0].source[1]`
+    pub field: String,
+    /// The (repo relative) file path it refers to
+    pub path: RelativePathBuf,
+    pub problem: Problem,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub struct BrokenReferenceCollection {
+    pub broken: Vec<BrokenReference>,
+}
+
+impl fmt::Display for BrokenReferenceCollection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for reference in &self.broken {
+            writeln!(f, "\t{reference}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects all the file references of a manifest,
+/// together with the paths of the properties they come from.
+/// The manifest is read as a generic structure,
+/// so this works even if it does not adhere to the schema;
+/// values of the wrong type are left out,
+/// as the schema validation reports them already.
+#[derive(Debug, Default)]
+struct References<'a> {
+    refs: Vec<(String, &'a str)>,
+}
+
+impl<'a> References<'a> {
+    fn single(&mut self, parent: &str, mapping: &'a Value, key: &str) {
+        if let Some(path) = mapping.get(key).and_then(Value::as_str) {
+            self.refs.push((spans::join_key(parent, key), path));
+        }
+    }
+
+    fn list(&mut self, parent: &str, mapping: &'a Value, key: &str) {
+        let field = spans::join_key(parent, key);
+        for (index, item) in items(mapping, key).iter().enumerate() {
+            if let Some(path) = item.as_str() {
+                self.refs.push((spans::join_index(&field, index), path));
+            }
+        }
+    }
+
+    fn parts(&mut self, parent: &str, mapping: &'a Value) {
+        let field = spans::join_key(parent, "part");
+        for (index, part) in items(mapping, "part").iter().enumerate() {
+            let part_field = spans::join_index(&field, index);
+            self.list(&part_field, part, "image");
+            self.list(&part_field, part, "source");
+            self.list(&part_field, part, "export");
+            self.list(&part_field, part, "auxiliary");
+            self.parts(&part_field, part);
+        }
+    }
+
+    fn of(manifest: &'a Value) -> Self {
+        let mut refs = Self::default();
+        refs.single("", manifest, "readme");
+        refs.single("", manifest, "contribution-guide");
+        refs.list("", manifest, "image");
+        refs.single("", manifest, "bom");
+        refs.single("", manifest, "user-manual");
+        refs.list("", manifest, "manufacturing-instructions");
+        refs.list("", manifest, "source");
+        refs.list("", manifest, "export");
+        refs.list("", manifest, "auxiliary");
+        refs.parts("", manifest);
+        refs
+    }
+}
+
+/// The items of the list at `key`,
+/// or none if there is no list.
+fn items<'a>(mapping: &'a Value, key: &str) -> &'a [Value] {
+    mapping
+        .get(key)
+        .and_then(Value::as_array)
+        .map_or(&[], Vec::as_slice)
+}
+
+/// Checks a single file reference.
+/// Absolute URLs are not files within the repository,
+/// so they are not checked here.
+fn check_path(path: &RelativePath, repo_dir: &Path) -> Option<Problem> {
+    if Url::parse(path.as_str()).is_ok() {
+        log::debug!("Not checking '{path}' for existence, as it is a URL");
+        None
+    } else if path.normalize().starts_with("..") {
+        Some(Problem::OutsideRepo)
+    } else if path.to_logical_path(repo_dir).exists() {
+        None
+    } else {
+        Some(Problem::Missing)
+    }
+}
+
+/// Checks that all the files referred to by `manifest` exist,
+/// resolving them against `repo_dir`,
+/// which is usually the directory containing the manifest.
+///
+/// # Errors
+///
+/// Lists each file reference that is broken.
+pub fn check(manifest: &Value, repo_dir: &Path) -> Result<(), BrokenReferenceCollection> {
+    log::debug!(
+        "Checking the file references against '{}' ...",
+        repo_dir.display()
+    );
+    let broken: Vec<_> = References::of(manifest)
+        .refs
+        .into_iter()
+        .filter_map(|(field, path)| {
+            let rel_path = RelativePathBuf::from(path);
+            check_path(&rel_path, repo_dir).map(|problem| BrokenReference {
+                field,
+                path: rel_path,
+                problem,
+            })
+        })
+        .collect();
+    if broken.is_empty() {
+        Ok(())
+    } else {
+        Err(BrokenReferenceCollection { broken })
+    }
+}
@@ -5,15 +5,21 @@
 // use jsonschema::{Draft, JSONSchema};
 use jsonschema::{Draft, Validator};
 // use serde_json::json;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 
 use std::{
     fmt, fs,
     path::{Path, PathBuf},
 };
 
+use crate::formats::spans::{self, Spans};
+use crate::formats::v2;
 use crate::license;
 
+pub mod files;
+pub mod report;
+pub mod urls;
+
 const SCHEMA_OKH_LOSH: &str = include_str!(concat!(
     env!("CARGO_MANIFEST_DIR"),
     "/resources/okh/src/schema/okh.schema.json"
@@ -23,7 +29,7 @@ const SCHEMA_OKH_V1: &str = include_str!(concat!(
     "/resources/okh/src/schema/okh-v1.schema.json"
 ));
 
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, Debug, strum_macros::IntoStaticStr)]
 pub enum Error {
     #[error("Failed to find any manifests.")]
     NoManifestsFound,
@@ -49,6 +55,110 @@ pub enum Error {
 
     #[error("License issue:\n{0:#}")]
     License(#[from] license::Error),
+
+    #[error("Broken file reference(s):\n{0:#}")]
+    BrokenFileReferences(#[from] files::BrokenReferenceCollection),
+
+    #[error("Unreachable URL(s):\n{0:#}")]
+    UnreachableUrls(#[from] urls::UnreachableUrlCollection),
+
+    #[error("Several kinds of problems:\n{0:#}")]
+    Several(#[from] ProblemCollection),
+}
+
+/// All the problems found in a single manifest,
+/// if they are of more than one kind
+/// (e.g. schema violations and broken file references).
+#[derive(thiserror::Error, Debug)]
+pub struct ProblemCollection {
+    pub problems: Vec<Error>,
+}
+
+impl fmt::Display for ProblemCollection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for problem in &self.problems {
+            writeln!(f, "{problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ProblemCollection {
+    /// Turns the problems into a result;
+    /// a single problem is returned as is.
+    fn into_result(mut self) -> Result<(), Error> {
+        match self.problems.len() {
+            0 => Ok(()),
+            1 => Err(self.problems.remove(0)),
+            _ => Err(self.into()),
+        }
+    }
+}
+
+impl Error {
+    /// The schema validation failures this error consists of, if any.
+    pub fn schema_failures(&self) -> Vec<&JsonSchemaValidationError> {
+        match self {
+            Self::InvalidContent(failure) => vec![failure],
+            Self::ValidationFailure(collection) => collection.failed_reqs.iter().collect(),
+            Self::Several(collection) => collection
+                .problems
+                .iter()
+                .flat_map(Self::schema_failures)
+                .collect(),
+            Self::NoManifestsFound
+            | Self::Io(_)
+            | Self::TomlParseFailure(_)
+            | Self::JsonParseFailure(_)
+            | Self::YamlParseFailure(_)
+            | Self::License(_)
+            | Self::BrokenFileReferences(_)
+            | Self::UnreachableUrls(_) => vec![],
+        }
+    }
+
+    /// Adds to each schema validation failure
+    /// where the failed value is located in `text`,
+    /// the manifest it was read from.
+    #[must_use]
+    fn locate(mut self, text: &str, spans: &Spans) -> Self {
+        match &mut self {
+            Self::InvalidContent(failure) => failure.locate(text, spans),
+            Self::ValidationFailure(collection) => {
+                for failure in &mut collection.failed_reqs {
+                    failure.locate(text, spans);
+                }
+            }
+            Self::Several(collection) => {
+                collection.problems = collection
+                    .problems
+                    .drain(..)
+                    .map(|problem| problem.locate(text, spans))
+                    .collect();
+            }
+            Self::NoManifestsFound
+            | Self::Io(_)
+            | Self::TomlParseFailure(_)
+            | Self::JsonParseFailure(_)
+            | Self::YamlParseFailure(_)
+            | Self::License(_)
+            | Self::BrokenFileReferences(_)
+            | Self::UnreachableUrls(_) => (),
+        }
+        self
+    }
+}
+
+/// Settings for the optional checks that go beyond the JSON schema.
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// Check that the files referred to by OKH LOSH manifests exist
+    /// within the directory of the manifest,
+    /// and do not point outside of it
+    pub check_files: bool,
+    /// Check that the URLs referred to by OKH LOSH manifests are reachable;
+    /// shared by all the manifests of a run
+    pub url_checker: Option<Arc<urls::Checker>>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -77,8 +187,9 @@ impl From<(PathBuf, Error)> for ErrorCollection {
 }
 
 #[derive(thiserror::Error, Debug)]
-#[error("Error:\n\tKind:    {kind:?}\n\tWhere:   {instance_path}\n\tContent: {instance}\n")]
 pub struct JsonSchemaValidationError {
+    /// Human readable description of the failure.
+    pub message: String,
     /// Value of the property that failed validation.
     pub instance: serde_json::Value,
     /// Type of validation error.
@@ -87,15 +198,56 @@ pub struct JsonSchemaValidationError {
     pub instance_path: jsonschema::paths::Location,
     /// Path to the JSON Schema keyword that failed validation.
     pub schema_path: jsonschema::paths::Location,
+    /// Where the value that failed validation is located
+    /// in the manifest file, if known.
+    pub location: Option<spans::Location>,
+    /// The line of the manifest file containing the value,
+    /// annotated with the failure, if the location is known.
+    pub snippet: Option<String>,
+}
+
+impl JsonSchemaValidationError {
+    /// Finds the value that failed validation in `text`,
+    /// the manifest it was read from.
+    fn locate(&mut self, text: &str, spans: &Spans) {
+        let path = spans::from_json_pointer(self.instance_path.as_str());
+        self.location = spans.get_closest(&path);
+        self.snippet = self
+            .location
+            .map(|location| spans::annotate(text, location, &self.message));
+    }
+}
+
+impl fmt::Display for JsonSchemaValidationError {
+    #[allow(clippy::use_debug)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Error:")?;
+        writeln!(f, "\tKind:    {:?}", self.kind)?;
+        write!(f, "\tWhere:   {}", self.instance_path)?;
+        if let Some(location) = &self.location {
+            write!(f, " ({location})")?;
+        }
+        writeln!(f)?;
+        writeln!(f, "\tContent: {}", self.instance)?;
+        if let Some(snippet) = &self.snippet {
+            for line in snippet.lines() {
+                writeln!(f, "\t{line}")?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<'a> From<jsonschema::ValidationError<'a>> for JsonSchemaValidationError {
     fn from(err: jsonschema::ValidationError<'a>) -> Self {
         Self {
+            message: err.to_string(),
             instance: err.instance.into_owned(),
             kind: err.kind,
             instance_path: err.instance_path,
             schema_path: err.schema_path,
+            location: None,
+            snippet: None,
         }
     }
 }
@@ -127,11 +279,14 @@ impl<'a> From<jsonschema::ErrorIterator<'a>> for JsonSchemaValidationErrorCollec
 
 /// Use this if you evaluate multiple contents (usually files)
 /// with the same schema.
+/// Reports all the failures, not just the first one.
 pub fn with_schema(schema: &Validator, content: &serde_json::Value) -> Result<(), Error> {
-    schema
-        .validate(content)
-        .map_err(JsonSchemaValidationError::from)
-        .map_err(std::convert::Into::into)
+    let mut failures = JsonSchemaValidationErrorCollection::from(schema.iter_errors(content));
+    match failures.failed_reqs.len() {
+        0 => Ok(()),
+        1 => Err(failures.failed_reqs.remove(0).into()),
+        _ => Err(failures.into()),
+    }
     // let result = schema.validate(content);
     // if let Err(errors) = result {
     //     for error in errors {
@@ -144,56 +299,189 @@ pub fn with_schema(schema: &Validator, content: &serde_json::Value) -> Result<()
     // Ok(())
 }
 
-pub fn okh_losh_toml<IP>(toml_path: IP) -> Result<(), Error>
-where
-    IP: AsRef<Path>,
-{
+/// Validates an OKH LOSH manifest,
+/// already parsed into a generic structure.
+/// `manifest_path` is the file the manifest was read from, if any;
+/// file references are resolved against its directory.
+fn okh_losh(
+    instance: &serde_json::Value,
+    manifest_path: Option<&Path>,
+    options: &Options,
+) -> Result<(), Error> {
     static RAW_SCHEMA: LazyLock<serde_json::Value> = LazyLock::new(|| {
         serde_json::from_str::<serde_json::Value>(SCHEMA_OKH_LOSH)
             .expect("The OKH-LOSH JSON schema contained within the binary is invalid JSON :/")
     });
 
+    let validator = jsonschema::options()
+        .with_draft(Draft::Draft7)
+        .build(&RAW_SCHEMA)
+        .map_err(JsonSchemaValidationError::from)?;
+    // NOTE We do not stop at the first kind of problem,
+    //      so all of them end up in the validation report.
+    let mut problems = ProblemCollection { problems: vec![] };
+    if let Err(err) = with_schema(&validator, instance) {
+        problems.problems.push(err);
+    }
+
+    if let Some(license_str) = instance.get("license").and_then(|v| v.as_str())
+        && let Err(err) = license::validate_spdx_expr(license_str, false)
+    {
+        problems.problems.push(err.into());
+    }
+
+    if options.check_files {
+        if let Some(path) = manifest_path {
+            if let Err(err) = files::check(instance, manifest_dir(path)) {
+                problems.problems.push(err.into());
+            }
+        } else {
+            log::warn!("Not checking file references, as the manifest was not read from a file");
+        }
+    }
+    if let Some(checker) = &options.url_checker
+        && let Err(err) = check_urls(instance, manifest_path, checker)
+    {
+        problems.problems.push(err);
+    }
+
+    problems.into_result()
+}
+
+/// Checks the URLs of a manifest,
+/// only warning about unreachable ones, unless configured otherwise.
+fn check_urls(
+    instance: &serde_json::Value,
+    manifest_path: Option<&Path>,
+    checker: &urls::Checker,
+) -> Result<(), Error> {
+    match checker.check_manifest(instance) {
+        Err(unreachable) if checker.options().unreachable_is_error => Err(unreachable.into()),
+        Err(unreachable) => {
+            let source = manifest_path.map_or_else(
+                || "the manifest".to_owned(),
+                |path| format!("'{}'", path.display()),
+            );
+            for url in unreachable.unreachable {
+                log::warn!("Unreachable URL in {source}: {url}");
+            }
+            Ok(())
+        }
+        Ok(()) => Ok(()),
+    }
+}
+
+/// Puts an error and all its causes into a single line,
+/// as the top-level errors (e.g. of `reqwest`) often say little.
+fn error_chain(err: &dyn std::error::Error) -> String {
+    let mut msg = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        msg.push_str(": ");
+        msg.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    msg
+}
+
+/// The directory relative to which the references in a manifest are resolved.
+fn manifest_dir(manifest_path: &Path) -> &Path {
+    manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+}
+
+/// Validates an OKH LOSH manifest given as TOML string.
+pub fn okh_losh_toml_str(toml_str: &str) -> Result<(), Error> {
+    let instance = toml::from_str::<serde_json::Value>(toml_str)?;
+
+    okh_losh(&instance, None, &Options::default())
+        .map_err(|err| err.locate(toml_str, &Spans::from_toml(toml_str)))
+}
+
+/// Validates an OKH LOSH manifest given as JSON string.
+pub fn okh_losh_json_str(json_str: &str) -> Result<(), Error> {
+    let instance = serde_json::from_str::<serde_json::Value>(json_str)?;
+
+    okh_losh(&instance, None, &Options::default())
+}
+
+pub fn okh_losh_toml<IP>(toml_path: IP, options: &Options) -> Result<(), Error>
+where
+    IP: AsRef<Path>,
+{
     log::debug!(
         "Validating an OKH LOSH file ('{}') ...",
         toml_path.as_ref().as_os_str().to_str().unwrap()
     );
-    let toml_str = fs::read_to_string(toml_path)?;
+    let toml_str = fs::read_to_string(&toml_path)?;
     let instance = toml::from_str::<serde_json::Value>(&toml_str)?;
 
-    let validator = jsonschema::options()
-        .with_draft(Draft::Draft7)
-        .build(&RAW_SCHEMA)
-        .map_err(JsonSchemaValidationError::from)?;
-    with_schema(&validator, &instance)?;
+    okh_losh(&instance, Some(toml_path.as_ref()), options)
+        .map_err(|err| err.locate(&toml_str, &Spans::from_toml(&toml_str)))
+}
 
-    if let Some(license_str) = instance.get("license").and_then(|v| v.as_str()) {
-        license::validate_spdx_expr(license_str, false)?;
-    }
+pub fn okh_losh_json<IP>(json_path: IP, options: &Options) -> Result<(), Error>
+where
+    IP: AsRef<Path>,
+{
+    log::debug!(
+        "Validating an OKH LOSH JSON file ('{}') ...",
+        json_path.as_ref().display()
+    );
+    let json_str = fs::read_to_string(&json_path)?;
+    let instance = serde_json::from_str::<serde_json::Value>(&json_str)?;
 
-    Ok(())
+    okh_losh(&instance, Some(json_path.as_ref()), options)
 }
 
-pub fn okh_v1_yaml<IP>(yaml_path: IP) -> Result<(), Error>
+/// Validates an OKH LOSH manifest in either TOML or JSON format,
+/// chosen by the file extension.
+pub fn okh_losh_toml_or_json<IP>(manifest_path: IP, options: &Options) -> Result<(), Error>
 where
     IP: AsRef<Path>,
 {
+    let is_json = manifest_path
+        .as_ref()
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| v2::Okh::json_ext_matcher().is_match(ext));
+    if is_json {
+        okh_losh_json(manifest_path, options)
+    } else {
+        okh_losh_toml(manifest_path, options)
+    }
+}
+
+/// Validates an OKH v1 manifest given as YAML string.
+pub fn okh_v1_yaml_str(yaml_str: &str) -> Result<(), Error> {
     static RAW_SCHEMA: LazyLock<serde_json::Value> = LazyLock::new(|| {
         serde_json::from_str::<serde_json::Value>(SCHEMA_OKH_V1)
             .expect("The OKH-V1 JSON schema contained within the binary is invalid JSON :/")
     });
 
-    log::debug!(
-        "Validating an OKH v1 file ('{}') ...",
-        yaml_path.as_ref().as_os_str().to_str().unwrap()
-    );
-    let yaml_str = fs::read_to_string(yaml_path)?;
-    let instance = serde_yaml::from_str::<serde_json::Value>(&yaml_str)?;
+    let instance = serde_yaml::from_str::<serde_json::Value>(yaml_str)?;
 
     let validator = jsonschema::options()
         .with_draft(Draft::Draft7)
         .build(&RAW_SCHEMA)
         .map_err(JsonSchemaValidationError::from)?;
     with_schema(&validator, &instance)
+        .map_err(|err| err.locate(yaml_str, &Spans::from_yaml(yaml_str)))
+}
+
+pub fn okh_v1_yaml<IP>(yaml_path: IP) -> Result<(), Error>
+where
+    IP: AsRef<Path>,
+{
+    log::debug!(
+        "Validating an OKH v1 file ('{}') ...",
+        yaml_path.as_ref().as_os_str().to_str().unwrap()
+    );
+    let yaml_str = fs::read_to_string(yaml_path)?;
+
+    okh_v1_yaml_str(&yaml_str)
 }
 
 /* pub fn list_files_by_ext(dir: &Path, extension: &str) -> Result<Vec<PathBuf>, Error> { */
@@ -0,0 +1,347 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Machine readable validation reports (JSON, SARIF and `JUnit` XML),
+//! for scripts and CI.
+
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::formats::{SerError, spans};
+
+use super::{Error, JsonSchemaValidationError, error_chain};
+
+const TOOL_NAME: &str = env!("CARGO_PKG_NAME");
+const TOOL_VERSION: &str = env!("CARGO_PKG_VERSION");
+const TOOL_URI: &str = env!("CARGO_PKG_REPOSITORY");
+
+/// The format of a validation report.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, strum_macros::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum Format {
+    /// Our own JSON structure
+    Json,
+    /// Static Analysis Results Interchange Format 2.1.0,
+    /// understood by many code hosting platforms
+    Sarif,
+    /// `JUnit` XML, understood by most CI systems;
+    /// each manifest is one test-case
+    Junit,
+}
+
+/// A single problem found in a manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Finding {
+    /// What kind of check failed, e.g. `schema`, `license` or `file-reference`
+    pub category: &'static str,
+    pub message: String,
+    /// JSON pointer to the offending value within the manifest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance_path: Option<String>,
+    /// JSON pointer to the violated JSON schema keyword
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_path: Option<String>,
+    /// The violated JSON schema keyword, e.g. `required` or `type`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// The offending value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+    /// Where the offending value is located in the manifest file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<spans::Location>,
+}
+
+impl Finding {
+    const fn new(category: &'static str, message: String, instance_path: Option<String>) -> Self {
+        Self {
+            category,
+            message,
+            instance_path,
+            schema_path: None,
+            kind: None,
+            value: None,
+            location: None,
+        }
+    }
+
+    fn schema(err: &JsonSchemaValidationError) -> Self {
+        let schema_path = err.schema_path.as_str();
+        Self {
+            category: "schema",
+            message: err.message.clone(),
+            instance_path: Some(err.instance_path.as_str().to_owned()),
+            schema_path: Some(schema_path.to_owned()),
+            kind: schema_path.rsplit('/').next().map(ToOwned::to_owned),
+            value: Some(err.instance.clone()),
+            location: err.location,
+        }
+    }
+
+    /// Splits a validation error into the individual problems it consists of.
+    pub fn all_of(err: &Error) -> Vec<Self> {
+        match err {
+            Error::InvalidContent(_) | Error::ValidationFailure(_) => err
+                .schema_failures()
+                .into_iter()
+                .map(Self::schema)
+                .collect(),
+            Error::Several(collection) => {
+                collection.problems.iter().flat_map(Self::all_of).collect()
+            }
+            Error::License(license_err) => vec![Self::new(
+                "license",
+                error_chain(license_err),
+                Some("/license".to_owned()),
+            )],
+            Error::BrokenFileReferences(collection) => collection
+                .broken
+                .iter()
+                .map(|reference| {
+                    Self::new(
+                        "file-reference",
+                        reference.to_string(),
+                        Some(spans::to_json_pointer(&reference.field)),
+                    )
+                })
+                .collect(),
+            Error::UnreachableUrls(collection) => collection
+                .unreachable
+                .iter()
+                .map(|url| {
+                    Self::new(
+                        "url",
+                        url.to_string(),
+                        Some(spans::to_json_pointer(&url.field)),
+                    )
+                })
+                .collect(),
+            Error::NoManifestsFound | Error::Io(_) => vec![Self::new("io", error_chain(err), None)],
+            Error::TomlParseFailure(_)
+            | Error::JsonParseFailure(_)
+            | Error::YamlParseFailure(_) => vec![Self::new("parse", error_chain(err), None)],
+        }
+    }
+}
+
+/// The validation result of a single manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ManifestReport {
+    pub file: PathBuf,
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<Finding>,
+}
+
+/// The validation results of all the manifests of a run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Report {
+    pub manifests: Vec<ManifestReport>,
+}
+
+impl Report {
+    /// Adds the result of validating `file`;
+    /// `err` is `None` if it is valid.
+    pub fn add(&mut self, file: &Path, err: Option<&Error>) {
+        self.manifests.push(ManifestReport {
+            file: file.to_path_buf(),
+            valid: err.is_none(),
+            errors: err.map(Finding::all_of).unwrap_or_default(),
+        });
+    }
+
+    pub fn num_invalid(&self) -> usize {
+        self.manifests
+            .iter()
+            .filter(|manifest| !manifest.valid)
+            .count()
+    }
+
+    /// Serializes the report in our own JSON structure.
+    ///
+    /// # Errors
+    ///
+    /// If serialization fails.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Serializes the report as SARIF 2.1.0.
+    ///
+    /// # Errors
+    ///
+    /// If serialization fails.
+    pub fn to_sarif(&self) -> Result<String, serde_json::Error> {
+        let results: Vec<_> = self
+            .manifests
+            .iter()
+            .flat_map(|manifest| {
+                let uri = manifest.file.to_string_lossy().replace('\\', "/");
+                manifest.errors.iter().map(move |finding| {
+                    let logical_locations: Vec<_> = finding
+                        .instance_path
+                        .iter()
+                        .map(|instance_path| {
+                            json!({
+                                "fullyQualifiedName": instance_path,
+                                "kind": "member",
+                            })
+                        })
+                        .collect();
+                    let mut physical_location = json!({
+                        "artifactLocation": { "uri": uri },
+                    });
+                    if let Some(location) = finding.location
+                        && let Some(physical_location_map) = physical_location.as_object_mut()
+                    {
+                        physical_location_map.insert(
+                            "region".to_owned(),
+                            json!({
+                            "startLine": location.line,
+                            "startColumn": location.column,
+                            }),
+                        );
+                    }
+                    let location = json!({
+                        "physicalLocation": physical_location,
+                        "logicalLocations": logical_locations,
+                    });
+                    let rule_id = finding.kind.as_ref().map_or_else(
+                        || finding.category.to_owned(),
+                        |kind| format!("{}/{kind}", finding.category),
+                    );
+                    json!({
+                        "ruleId": rule_id,
+                        "level": "error",
+                        "message": { "text": finding.message },
+                        "locations": [location],
+                        "properties": {
+                            "category": finding.category,
+                            "instancePath": finding.instance_path,
+                            "schemaPath": finding.schema_path,
+                            "value": finding.value,
+                        },
+                    })
+                })
+            })
+            .collect();
+        let sarif = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": TOOL_NAME,
+                        "version": TOOL_VERSION,
+                        "informationUri": TOOL_URI,
+                    },
+                },
+                "results": results,
+            }],
+        });
+        serde_json::to_string_pretty(&sarif)
+    }
+
+    /// Serializes the report as `JUnit` XML,
+    /// with one test-case per manifest.
+    pub fn to_junit(&self) -> String {
+        let tests = self.manifests.len();
+        let failures = self.num_invalid();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"{TOOL_NAME} val\" tests=\"{tests}\" failures=\"{failures}\">"
+        );
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{TOOL_NAME} val\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\">"
+        );
+        for manifest in &self.manifests {
+            let name = xml_escape(&manifest.file.to_string_lossy());
+            if manifest.errors.is_empty() {
+                let _ = writeln!(
+                    xml,
+                    "    <testcase classname=\"{TOOL_NAME}.val\" name=\"{name}\"/>"
+                );
+                continue;
+            }
+            let _ = writeln!(
+                xml,
+                "    <testcase classname=\"{TOOL_NAME}.val\" name=\"{name}\">"
+            );
+            let first = manifest.errors.first();
+            let _ = write!(
+                xml,
+                "      <failure type=\"{}\" message=\"{}\">",
+                first.map_or("", |finding| finding.category),
+                xml_escape(&format!(
+                    "{} problem(s), the first: {}",
+                    manifest.errors.len(),
+                    first.map_or("", |finding| finding.message.as_str())
+                ))
+            );
+            for finding in &manifest.errors {
+                let _ = write!(xml, "\n[{}]", finding.category);
+                if let Some(instance_path) = &finding.instance_path {
+                    let _ = write!(xml, " {}", xml_escape(instance_path));
+                }
+                if let Some(location) = finding.location {
+                    let _ = write!(xml, " ({location})");
+                }
+                if finding.instance_path.is_some() || finding.location.is_some() {
+                    xml.push(':');
+                }
+                let _ = write!(xml, " {}", xml_escape(&finding.message));
+            }
+            xml.push_str("\n      </failure>\n    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+
+    /// Writes the report in the given format to `file`.
+    ///
+    /// # Errors
+    ///
+    /// If serializing or writing fails.
+    pub fn write(&self, format: Format, file: &Path) -> Result<(), SerError> {
+        let serialized = match format {
+            Format::Json => self.to_json()?,
+            Format::Sarif => self.to_sarif()?,
+            Format::Junit => self.to_junit(),
+        };
+        log::debug!("Writing the validation report to '{}' ...", file.display());
+        fs::write(file, serialized)?;
+        Ok(())
+    }
+}
+
+/// Escapes text for use in XML content and attribute values.
+/// Control characters are not allowed in XML 1.0 (not even escaped),
+/// so they are replaced with U+FFFD.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for chr in text.chars() {
+        match chr {
+            '\t' | '\n' | '\r' => escaped.push(chr),
+            '\u{0}'..='\u{1f}' => escaped.push(char::REPLACEMENT_CHARACTER),
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(chr),
+        }
+    }
+    escaped
+}
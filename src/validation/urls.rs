@@ -0,0 +1,480 @@
+// SPDX-FileCopyrightText: 2025 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Checks whether the URLs an OKH LOSH manifest refers to are reachable,
+//! remembering the results for a while in a cache file,
+//! so repeated validation runs do not hammer the same servers.
+
+use std::{
+    collections::HashMap,
+    env, fmt, fs,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use url::Url;
+
+use crate::formats::spans;
+
+use super::error_chain;
+
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Failed to create the HTTP client")]
+    Client(#[from] reqwest::Error),
+
+    #[error("Failed to create the thread pool for checking URLs")]
+    ThreadPool(#[from] rayon::ThreadPoolBuildError),
+
+    #[error("Failed to read or write the URL check cache")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to (de)serialize the URL check cache")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Settings for checking URLs.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// How long to wait for a server to respond
+    pub timeout: Duration,
+    /// How many URLs to check in parallel; 0 means one per CPU core
+    pub jobs: usize,
+    /// Where to store the results; `None` disables the cache
+    pub cache_file: Option<PathBuf>,
+    /// How long a cached result is trusted
+    pub ttl: Duration,
+    /// Whether unreachable URLs make a manifest invalid,
+    /// or are only warned about
+    pub unreachable_is_error: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            jobs: 8,
+            cache_file: default_cache_file(),
+            ttl: Duration::from_hours(24),
+            unreachable_is_error: false,
+        }
+    }
+}
+
+/// The cache file within the users cache dir,
+/// following the XDG Base Directory Specification.
+pub fn default_cache_file() -> Option<PathBuf> {
+    env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|cache_dir| {
+            cache_dir
+                .join(env!("CARGO_PKG_NAME"))
+                .join("url-check.json")
+        })
+}
+
+/// The result of checking a single URL.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum Status {
+    /// The server responded with a success status
+    Reachable { code: u16 },
+    /// The server responded, but with an error status (e.g. 404)
+    HttpError { code: u16 },
+    /// No response was received (e.g. unknown host or timeout)
+    Failed { reason: String },
+}
+
+impl Status {
+    pub const fn is_reachable(&self) -> bool {
+        matches!(self, Self::Reachable { .. })
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reachable { code } => write!(f, "is reachable (HTTP status {code})"),
+            Self::HttpError { code } => write!(f, "responded with HTTP status {code}"),
+            Self::Failed { reason } => write!(f, "could not be reached: {reason}"),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("'{field}': <{url}> {status}")]
+pub struct UnreachableUrl {
+    /// The path of the property in the manifest,
+    /// for example `software[0].release`
+    pub field: String,
+    pub url: Url,
+    pub status: Status,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub struct UnreachableUrlCollection {
+    pub unreachable: Vec<UnreachableUrl>,
+}
+
+impl fmt::Display for UnreachableUrlCollection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for url in &self.unreachable {
+            writeln!(f, "\t{url}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    status: Status,
+    /// When the URL was checked, in seconds since the UNIX epoch
+    checked: u64,
+}
+
+/// Collects all the URLs of a manifest,
+/// together with the paths of the properties they come from.
+/// The manifest is read as a generic structure,
+/// so this works even if it does not adhere to the schema;
+/// values that are not URLs are left out,
+/// as the schema validation reports them already.
+fn urls_of(manifest: &Value) -> Vec<(String, Url)> {
+    let url_at = |value: Option<&Value>| {
+        value
+            .and_then(Value::as_str)
+            .and_then(|url_str| Url::parse(url_str).ok())
+    };
+    let list = |key: &str| {
+        manifest
+            .get(key)
+            .and_then(Value::as_array)
+            .map_or(&[][..], Vec::as_slice)
+    };
+    let mut urls = vec![];
+    for key in ["repo", "release", "fork-of"] {
+        if let Some(url) = url_at(manifest.get(key)) {
+            urls.push((key.to_owned(), url));
+        }
+    }
+    for (index, attestation) in list("attestation").iter().enumerate() {
+        if let Some(url) = url_at(Some(attestation)) {
+            urls.push((spans::join_index("attestation", index), url));
+        }
+    }
+    for (index, software) in list("software").iter().enumerate() {
+        if let Some(url) = url_at(software.get("release")) {
+            let field = spans::join_key(&spans::join_index("software", index), "release");
+            urls.push((field, url));
+        }
+    }
+    urls
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs())
+}
+
+/// Checks URLs, and keeps the results around.
+/// One instance is meant to be shared by all the manifests of a run,
+/// so each URL is only requested once.
+#[derive(Debug)]
+pub struct Checker {
+    client: reqwest::blocking::Client,
+    pool: rayon::ThreadPool,
+    options: Options,
+    cache: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl Checker {
+    /// Creates a checker, and loads the cache file, if there is one.
+    /// A broken cache file is ignored, and replaced when saving.
+    ///
+    /// # Errors
+    ///
+    /// If the HTTP client or the thread pool can not be created.
+    pub fn new(options: Options) -> Result<Self, Error> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(options.timeout)
+            .user_agent(USER_AGENT)
+            .build()?;
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(options.jobs)
+            .build()?;
+        let cache = options
+            .cache_file
+            .as_ref()
+            .filter(|cache_file| cache_file.exists())
+            .map_or_else(
+                || Ok(HashMap::new()),
+                |cache_file| {
+                    log::debug!("Loading the URL check cache '{}' ...", cache_file.display());
+                    Ok::<_, Error>(serde_json::from_str(&fs::read_to_string(cache_file)?)?)
+                },
+            )
+            .unwrap_or_else(|err| {
+                log::warn!("Ignoring the URL check cache: {}", error_chain(&err));
+                HashMap::new()
+            });
+        Ok(Self {
+            client,
+            pool,
+            options,
+            cache: Mutex::new(cache),
+        })
+    }
+
+    pub const fn options(&self) -> &Options {
+        &self.options
+    }
+
+    fn request(&self, url: &Url) -> Status {
+        log::debug!("Checking URL <{url}> ...");
+        // NOTE Some servers do not support HEAD requests,
+        //      or answer them differently than GET requests,
+        //      so we only trust a successful HEAD response.
+        //      If the server could not be reached at all though,
+        //      a GET request would only fail the same way.
+        match self.client.head(url.clone()).send() {
+            Ok(response) if response.status().is_success() => {
+                return Status::Reachable {
+                    code: response.status().as_u16(),
+                };
+            }
+            Ok(_) => (),
+            Err(err) => {
+                return Status::Failed {
+                    reason: error_chain(&err),
+                };
+            }
+        }
+        match self.client.get(url.clone()).send() {
+            Ok(response) if response.status().is_success() => Status::Reachable {
+                code: response.status().as_u16(),
+            },
+            Ok(response) => Status::HttpError {
+                code: response.status().as_u16(),
+            },
+            Err(err) => Status::Failed {
+                reason: error_chain(&err),
+            },
+        }
+    }
+
+    /// Checks a single URL, unless a recent enough result is cached.
+    /// Failures to connect are not cached,
+    /// as they are often only temporary.
+    pub fn check(&self, url: &Url) -> Status {
+        let now = now();
+        let max_age = self.options.ttl.as_secs();
+        let cached = self
+            .cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(url.as_str())
+            .filter(|entry| now.saturating_sub(entry.checked) < max_age)
+            .map(|entry| entry.status.clone());
+        if let Some(status) = cached {
+            log::trace!("Using the cached status of URL <{url}>: {status}");
+            return status;
+        }
+        let status = self.request(url);
+        if !matches!(status, Status::Failed { .. }) {
+            self.cache
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(
+                    url.to_string(),
+                    CacheEntry {
+                        status: status.clone(),
+                        checked: now,
+                    },
+                );
+        }
+        status
+    }
+
+    /// Checks all the URLs of a manifest, in parallel.
+    ///
+    /// # Errors
+    ///
+    /// Lists each URL that is not reachable.
+    pub fn check_manifest(&self, manifest: &Value) -> Result<(), UnreachableUrlCollection> {
+        let urls = urls_of(manifest);
+        let unreachable: Vec<_> = self.pool.install(|| {
+            urls.into_par_iter()
+                .filter_map(|(field, url)| {
+                    let status = self.check(&url);
+                    (!status.is_reachable()).then_some(UnreachableUrl { field, url, status })
+                })
+                .collect()
+        });
+        if unreachable.is_empty() {
+            Ok(())
+        } else {
+            Err(UnreachableUrlCollection { unreachable })
+        }
+    }
+
+    /// Writes the results that are still valid to the cache file.
+    ///
+    /// # Errors
+    ///
+    /// If serializing or writing fails.
+    pub fn save_cache(&self) -> Result<(), Error> {
+        let Some(cache_file) = &self.options.cache_file else {
+            return Ok(());
+        };
+        let now = now();
+        let max_age = self.options.ttl.as_secs();
+        let serialized = {
+            let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+            cache.retain(|_, entry| now.saturating_sub(entry.checked) < max_age);
+            serde_json::to_string_pretty(&*cache)?
+        };
+        log::debug!("Writing the URL check cache '{}' ...", cache_file.display());
+        if let Some(cache_dir) = cache_file.parent() {
+            fs::create_dir_all(cache_dir)?;
+        }
+        fs::write(cache_file, serialized)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+    };
+
+    use super::*;
+
+    /// Serves HTTP on a local port,
+    /// answering each request with the status code `respond` returns for its method.
+    /// Returns the URL of the server, and a counter of the requests served.
+    fn serve(respond: fn(&str) -> u16) -> (Url, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!(
+            "http://{}/okh.toml",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&requests);
+        thread::spawn(move || {
+            for incoming in listener.incoming() {
+                let mut stream = incoming.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+                let method = request_line.split(' ').next().unwrap_or_default();
+                let code = respond(method);
+                write!(
+                    stream,
+                    "HTTP/1.1 {code} Whatever\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )
+                .unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn options(cache_file: Option<PathBuf>) -> Options {
+        Options {
+            timeout: Duration::from_secs(5),
+            jobs: 1,
+            cache_file,
+            ttl: Duration::from_hours(1),
+            unreachable_is_error: false,
+        }
+    }
+
+    /// A cache file path that is unique to the test and this process.
+    fn temp_cache_file(test: &str) -> PathBuf {
+        let cache_file = env::temp_dir().join(format!(
+            "{}-{test}-{}.json",
+            env!("CARGO_PKG_NAME"),
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&cache_file);
+        cache_file
+    }
+
+    #[test]
+    fn falls_back_to_get_if_head_is_not_allowed() {
+        let (url, requests) = serve(|method| if method == "HEAD" { 405 } else { 200 });
+        let checker = Checker::new(options(None)).unwrap();
+        assert_eq!(checker.check(&url), Status::Reachable { code: 200 });
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn reports_not_found_as_http_error() {
+        let (url, _requests) = serve(|_method| 404);
+        let checker = Checker::new(options(None)).unwrap();
+        assert_eq!(checker.check(&url), Status::HttpError { code: 404 });
+    }
+
+    #[test]
+    fn does_not_cache_refused_connections() {
+        let url = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            Url::parse(&format!(
+                "http://{}/okh.toml",
+                listener.local_addr().unwrap()
+            ))
+            .unwrap()
+        };
+        let cache_file = temp_cache_file("refused");
+        let checker = Checker::new(options(Some(cache_file.clone()))).unwrap();
+        assert!(matches!(checker.check(&url), Status::Failed { .. }));
+        checker.save_cache().unwrap();
+        let cache: HashMap<String, CacheEntry> =
+            serde_json::from_str(&fs::read_to_string(&cache_file).unwrap()).unwrap();
+        fs::remove_file(&cache_file).unwrap();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn requests_again_once_the_cached_entry_expired() {
+        let (url, requests) = serve(|_method| 404);
+        let cache_file = temp_cache_file("ttl");
+        let cached = |age: Duration| CacheEntry {
+            status: Status::Reachable { code: 200 },
+            checked: now() - age.as_secs(),
+        };
+        let fresh_url = url.join("fresh.toml").unwrap();
+        let cache = HashMap::from([
+            (url.to_string(), cached(Duration::from_hours(2))),
+            (fresh_url.to_string(), cached(Duration::from_mins(1))),
+        ]);
+        fs::write(&cache_file, serde_json::to_string(&cache).unwrap()).unwrap();
+        let checker = Checker::new(options(Some(cache_file.clone()))).unwrap();
+        fs::remove_file(&cache_file).unwrap();
+        assert_eq!(checker.check(&fresh_url), Status::Reachable { code: 200 });
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+        assert_eq!(checker.check(&url), Status::HttpError { code: 404 });
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+}